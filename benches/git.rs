@@ -0,0 +1,24 @@
+#![feature(test)]
+
+extern crate test;
+
+use git_view::{Git, GitTrait, LibGit};
+use test::{black_box, Bencher};
+
+/// The queries made by a plain `git view` on a branch, which are gathered into a snapshot
+fn view_branch(git: &impl GitTrait) {
+    black_box(git.get_head().unwrap());
+    black_box(git.get_config_list().unwrap());
+    black_box(git.get_refs().unwrap());
+}
+
+#[bench]
+fn command(bencher: &mut Bencher) {
+    bencher.iter(|| view_branch(&Git::default()));
+}
+
+#[bench]
+fn libgit2(bencher: &mut Bencher) {
+    // Opening the repository is part of every invocation of the binary
+    bencher.iter(|| view_branch(&LibGit::default()));
+}
@@ -1,17 +1,25 @@
-use std::panic::set_hook;
+use std::{
+    io::{self, IsTerminal},
+    panic::set_hook,
+    process,
+};
 
-use clap::{command, crate_authors, crate_description, crate_version, Arg, Command, ErrorKind};
+use clap::{crate_authors, crate_description, crate_version, Arg, Command};
+#[cfg(not(feature = "libgit2"))]
 use git_view::Git;
-use git_view::GitView;
+#[cfg(feature = "libgit2")]
+use git_view::LibGit;
+use git_view::{ErrorType, GitView, MissingPath, ResolveRef, Unpushed};
 
-macro_rules! clap_panic {
-    ($e:expr) => {
-        command!().error(ErrorKind::DisplayHelp, $e).exit()
-    };
+macro_rules! exit_with_error {
+    ($e:expr, $code:expr) => {{
+        eprintln!("error: {}", $e);
+        process::exit($code)
+    }};
 }
 
 fn main() {
-    set_hook(Box::new(|info| clap_panic!(info)));
+    set_hook(Box::new(|info| exit_with_error!(info, 1)));
 
     let matches = Command::new("git-view")
         .version(crate_version!())
@@ -26,6 +34,20 @@ fn main() {
                 .takes_value(true)
                 .display_order(1),
         )
+        .arg(
+            Arg::new("all-remotes")
+                .long_help("View on every remote instead of a single one")
+                .long("all-remotes")
+                .conflicts_with("remote")
+                .display_order(2),
+        )
+        .arg(
+            Arg::new("upstream")
+                .long_help("View the upstream repository that the fork was created from\n[default: upstream remote]")
+                .long("upstream")
+                .conflicts_with_all(&["remote", "all-remotes"])
+                .display_order(3),
+        )
         .arg(
             Arg::new("branch")
                 .long_help("The branch to view on GitHub\n[default: current branch]")
@@ -33,28 +55,28 @@ fn main() {
                 .long("branch")
                 .value_name("name")
                 .takes_value(true)
-                .display_order(2),
+                .display_order(4),
         )
         .arg(
             Arg::new("issue")
-                .long_help("The GitHub issue number\n[default: number from current branch]")
+                .long_help("The GitHub issue number or issue tracker key\n[default: number from current branch]")
                 .short('i')
                 .long("issue")
                 .value_name("number")
                 .default_missing_value("branch")
                 .conflicts_with("commit")
                 .takes_value(true)
-                .display_order(3),
+                .display_order(5),
         )
         .arg(
             Arg::new("commit")
-                .long_help("The commit to view on GitHub\n[default: current commit]")
+                .long_help("The commit or revision to view on GitHub\n[default: current commit]")
                 .short('c')
                 .long("commit")
                 .value_name("hash")
                 .default_missing_value("current")
                 .conflicts_with_all(&["remote", "branch"])
-                .display_order(4),
+                .display_order(6),
         )
         .arg(
             Arg::new("path")
@@ -67,26 +89,153 @@ fn main() {
                 .conflicts_with("issue")
                 .takes_value(true)
                 .value_hint(clap::ValueHint::AnyPath)
-                .display_order(5),
+                .display_order(7),
+        )
+        .arg(
+            Arg::new("origin-pr")
+                .long_help("View the pull request, or merge request on GitLab, that introduced the commit")
+                .long("origin-pr")
+                .requires("commit")
+                .conflicts_with("path")
+                .display_order(8),
+        )
+        .arg(
+            Arg::new("unpushed")
+                .long_help("What to do when the commit hasn't been pushed to the remote\n[default: warn] [possible values: warn, ancestor, refuse]")
+                .long("unpushed")
+                .value_name("action")
+                .possible_values(["warn", "ancestor", "refuse"])
+                .hide_possible_values(true)
+                .takes_value(true)
+                .display_order(9),
+        )
+        .arg(
+            Arg::new("missing-path")
+                .long_help("What to do when the path doesn't exist on the remote\n[default: refuse] [possible values: refuse, parent]")
+                .long("missing-path")
+                .value_name("action")
+                .possible_values(["refuse", "parent"])
+                .hide_possible_values(true)
+                .takes_value(true)
+                .requires("path")
+                .display_order(10),
+        )
+        .arg(
+            Arg::new("resolve")
+                .long_help("Print the local path and line of a file url on GitHub")
+                .long("resolve")
+                .value_name("url")
+                .takes_value(true)
+                .value_hint(clap::ValueHint::Url)
+                .conflicts_with_all(&[
+                    "remote",
+                    "all-remotes",
+                    "upstream",
+                    "branch",
+                    "issue",
+                    "commit",
+                    "path",
+                    "origin-pr",
+                ])
+                .display_order(11),
+        )
+        .arg(
+            Arg::new("resolve-ref")
+                .long_help("What to do with the ref of the resolved url\n[default: ignore] [possible values: ignore, verify, checkout]")
+                .long("resolve-ref")
+                .value_name("action")
+                .possible_values(["ignore", "verify", "checkout"])
+                .hide_possible_values(true)
+                .takes_value(true)
+                .requires("resolve")
+                .display_order(12),
+        )
+        .arg(
+            Arg::new("repo")
+                .long_help("The repository to view on GitHub\n[default: current working directory]")
+                .short('C')
+                .long("repo")
+                .value_name("dir")
+                .takes_value(true)
+                .value_hint(clap::ValueHint::DirPath)
+                .display_order(13),
         )
         .arg(
             Arg::new("print")
                 .long_help("Print URL instead of opening on GitHub")
                 .long("print")
-                .display_order(6),
+                .display_order(14),
+        )
+        .arg(
+            Arg::new("verbose")
+                .long_help("Print which branch, tag or commit a detached HEAD was resolved to")
+                .short('v')
+                .long("verbose")
+                .display_order(15),
         );
 
     let matches = matches.get_matches();
-    let git_view = GitView::new(
-        matches.value_of("branch"),
-        matches.value_of("remote"),
-        matches.value_of("commit"),
-        matches.value_of("issue"),
-        matches.value_of("path"),
-        matches.is_present("print"),
-    );
+    let git_view = GitView::builder()
+        .with_branch(matches.value_of("branch"))
+        .with_remote(matches.value_of("remote"))
+        .with_commit(matches.value_of("commit"))
+        .with_issue(matches.value_of("issue"))
+        .with_path(matches.value_of("path"))
+        .with_resolve(matches.value_of("resolve"))
+        .with_resolve_ref(match matches.value_of("resolve-ref") {
+            Some("verify") => ResolveRef::Verify,
+            Some("checkout") => ResolveRef::Checkout,
+            _ => ResolveRef::Ignore,
+        })
+        .with_unpushed(match matches.value_of("unpushed") {
+            Some("ancestor") => Unpushed::Ancestor,
+            Some("refuse") => Unpushed::Refuse,
+            _ => Unpushed::Warn,
+        })
+        .with_missing_path(match matches.value_of("missing-path") {
+            Some("parent") => MissingPath::Parent,
+            _ => MissingPath::Refuse,
+        })
+        .with_origin_pr(matches.is_present("origin-pr"))
+        .with_print(matches.is_present("print"))
+        .with_all_remotes(matches.is_present("all-remotes"))
+        .with_upstream(matches.is_present("upstream"))
+        .with_verbose(matches.is_present("verbose"))
+        .with_interactive(io::stdin().is_terminal() && io::stderr().is_terminal())
+        .build();
+
+    #[cfg(not(feature = "libgit2"))]
+    let git = Git::default();
+    #[cfg(feature = "libgit2")]
+    let git = LibGit::default();
+    let (git, repositories) = match matches.value_of("repo") {
+        Some(repo) => (git.with_directory(repo), Vec::new()),
+        None if matches.is_present("resolve") => match GitView::known_repositories(&git) {
+            Ok(repositories) => (git, repositories),
+            Err(app_error) => exit_with_error!(app_error, app_error.exit_code()),
+        },
+        None => (git, Vec::new()),
+    };
+
+    // A url to resolve is looked for in the known repositories when the current one can't have it
+    let mut result = git_view.view_repository(git);
+    for repository in repositories {
+        match &result {
+            Err(app_error)
+                if matches!(
+                    app_error.error_type(),
+                    ErrorType::MissingGitRepository | ErrorType::MissingGitRemote
+                ) => {}
+            _ => break,
+        }
+        #[cfg(not(feature = "libgit2"))]
+        let git = Git::default();
+        #[cfg(feature = "libgit2")]
+        let git = LibGit::default();
+        result = git_view.view_repository(git.with_directory(repository));
+    }
 
-    if let Err(app_error) = git_view.view_repository(Git) {
-        clap_panic!(app_error.error_str);
+    if let Err(app_error) = result {
+        exit_with_error!(app_error, app_error.exit_code());
     }
 }
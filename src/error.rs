@@ -1,38 +1,283 @@
-#[derive(Debug, PartialEq)]
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    io,
+    str::Utf8Error,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ErrorType {
     CommandFailed,
     CommandError,
     MissingGitRepository,
     MissingGitRemote,
     MissingDefaultBranch,
+    InvalidRevision,
+    PathOutsideRepository,
+    MissingPath,
+    MissingRemotePath,
+    BareRepository,
+    MissingPullRequest,
+    UnpushedCommit,
     InvalidGitUrl,
+    InvalidIssuePattern,
+    InvalidIssueTracker,
+    MissingIssueNumber,
+    AmbiguousIssueReference,
     InvalidUtf8,
     IOError,
+    BrowserLaunchFailed,
+}
+
+impl ErrorType {
+    /// The exit code of the process when it fails with this category of error, documented in the
+    /// README. Usage errors are reported by clap with exit code 2
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorType::CommandFailed
+            | ErrorType::CommandError
+            | ErrorType::InvalidUtf8
+            | ErrorType::IOError => 1,
+            ErrorType::MissingGitRepository => 3,
+            ErrorType::MissingGitRemote => 4,
+            ErrorType::InvalidGitUrl => 5,
+            ErrorType::MissingDefaultBranch
+            | ErrorType::InvalidRevision
+            | ErrorType::UnpushedCommit
+            | ErrorType::MissingPullRequest => 6,
+            ErrorType::PathOutsideRepository
+            | ErrorType::MissingPath
+            | ErrorType::MissingRemotePath => 7,
+            ErrorType::InvalidIssuePattern
+            | ErrorType::InvalidIssueTracker
+            | ErrorType::MissingIssueNumber
+            | ErrorType::AmbiguousIssueReference => 8,
+            ErrorType::BrowserLaunchFailed => 9,
+            ErrorType::BareRepository => 10,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct AppError {
-    pub error_type: ErrorType,
-    pub error_str: String,
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AppError {
+    /// No remote could be viewed, `remote` is the remote that was looked for, if any, and
+    /// `suggestion` the configured remote closest to it. Remotes go missing in too many ways for
+    /// the fields to describe, so `message` describes how, and the fixes are rendered from the
+    /// fields
+    MissingGitRemote {
+        remote: Option<String>,
+        suggestion: Option<String>,
+        message: String,
+    },
+    /// The url of a remote, or a url given to resolve, couldn't be parsed, with `message`
+    /// describing which of them it was
+    InvalidGitUrl {
+        url: String,
+        message: String,
+    },
+    /// Neither the remote branch nor the default branch of the remote could be found, with the
+    /// closest branch
+    MissingDefaultBranch {
+        remote: String,
+        branch: String,
+        suggestion: Option<String>,
+    },
+    /// The commit, and if `ancestors` is set any of its ancestors, hasn't been pushed to the
+    /// remote
+    UnpushedCommit {
+        commit: String,
+        remote: String,
+        ancestors: bool,
+    },
+    /// No pull request introducing the commit could be found
+    MissingPullRequest {
+        commit: String,
+    },
+    /// The path isn't on the remote ref, with the closest path that is
+    MissingRemotePath {
+        path: String,
+        reference: String,
+        suggestion: Option<String>,
+    },
+    /// The path lies outside of the repository
+    PathOutsideRepository {
+        path: String,
+    },
+    /// The url couldn't be opened in the browser
+    BrowserLaunchFailed {
+        url: String,
+        source: io::Error,
+    },
+    Io(io::Error),
+    InvalidUtf8(Utf8Error),
+    /// Any other error, described by its message
+    Other {
+        error_type: ErrorType,
+        message: String,
+    },
 }
 
-impl From<std::io::Error> for AppError {
-    fn from(error: std::io::Error) -> Self {
-        AppError::new(ErrorType::IOError, error.to_string())
+impl AppError {
+    /// Errors of categories without a typed variant, which are always [`AppError::Other`]
+    pub fn new(error_type: ErrorType, message: String) -> Self {
+        AppError::Other {
+            error_type,
+            message,
+        }
+    }
+
+    /// The category of the error
+    pub fn error_type(&self) -> ErrorType {
+        match self {
+            AppError::MissingGitRemote { .. } => ErrorType::MissingGitRemote,
+            AppError::InvalidGitUrl { .. } => ErrorType::InvalidGitUrl,
+            AppError::MissingDefaultBranch { .. } => ErrorType::MissingDefaultBranch,
+            AppError::UnpushedCommit { .. } => ErrorType::UnpushedCommit,
+            AppError::MissingPullRequest { .. } => ErrorType::MissingPullRequest,
+            AppError::MissingRemotePath { .. } => ErrorType::MissingRemotePath,
+            AppError::PathOutsideRepository { .. } => ErrorType::PathOutsideRepository,
+            AppError::BrowserLaunchFailed { .. } => ErrorType::BrowserLaunchFailed,
+            AppError::Io(_) => ErrorType::IOError,
+            AppError::InvalidUtf8(_) => ErrorType::InvalidUtf8,
+            AppError::Other { error_type, .. } => *error_type,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        self.error_type().exit_code()
     }
 }
 
-impl From<std::str::Utf8Error> for AppError {
-    fn from(error: std::str::Utf8Error) -> Self {
-        AppError::new(ErrorType::InvalidUtf8, error.to_string())
+impl Display for AppError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::MissingGitRemote {
+                remote,
+                suggestion,
+                message,
+            } => {
+                write!(f, "{message}")?;
+                write_suggestion(f, suggestion)?;
+                match (remote, suggestion) {
+                    (_, Some(suggestion)) => write!(
+                        f,
+                        "\nView it with '--remote {suggestion}' or make it the default with 'git config open.default.remote {suggestion}'"
+                    ),
+                    (Some(remote), None) => {
+                        write!(f, "\nSet it with 'git config remote.{remote}.url <url>'")
+                    }
+                    (None, None) => Ok(()),
+                }
+            }
+            AppError::InvalidGitUrl { message, .. } => write!(f, "{message}"),
+            AppError::MissingDefaultBranch {
+                remote,
+                branch,
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "Could not verify '{remote}/{branch}' exists and could not retrieve default branch"
+                )?;
+                write_suggestion(f, suggestion)?;
+                if let Some(suggestion) = suggestion {
+                    write!(f, "\nView it with '--branch {suggestion}'")?;
+                }
+                write!(
+                    f,
+                    "\nSet the default branch with 'git remote set-head {remote} --auto'"
+                )
+            }
+            AppError::UnpushedCommit {
+                commit,
+                remote,
+                ancestors: false,
+            } => write!(f, "Commit '{commit}' has not been pushed to '{remote}'"),
+            AppError::UnpushedCommit {
+                commit,
+                remote,
+                ancestors: true,
+            } => write!(
+                f,
+                "Commit '{commit}' and its ancestors have not been pushed to '{remote}'"
+            ),
+            AppError::MissingPullRequest { commit } => write!(
+                f,
+                "Could not find the pull request that introduced commit '{commit}'"
+            ),
+            AppError::MissingRemotePath {
+                path,
+                reference,
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "Path '{path}' does not exist on '{reference}', it may not have been committed or pushed"
+                )?;
+                write_suggestion(f, suggestion)?;
+                match suggestion {
+                    Some(suggestion) => write!(f, "\nView it with '--path {suggestion}'"),
+                    None => write!(
+                        f,
+                        "\nView its closest existing directory with '--missing-path parent'"
+                    ),
+                }
+            }
+            AppError::PathOutsideRepository { path } => {
+                write!(f, "Path '{path}' is outside of the repository")
+            }
+            AppError::BrowserLaunchFailed { url, source } => {
+                write!(f, "Could not open '{url}' in a browser: {source}")
+            }
+            AppError::Io(error) => write!(f, "{error}"),
+            AppError::InvalidUtf8(error) => write!(f, "{error}"),
+            AppError::Other { message, .. } => write!(f, "{message}"),
+        }
     }
 }
 
-impl AppError {
-    pub fn new(error_type: ErrorType, error_str: String) -> Self {
-        Self {
-            error_type,
-            error_str,
+// `io::Error` can't be compared, so errors are equal when their category and message are
+impl PartialEq for AppError {
+    fn eq(&self, other: &Self) -> bool {
+        self.error_type() == other.error_type() && self.to_string() == other.to_string()
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::BrowserLaunchFailed { source, .. } => Some(source),
+            AppError::Io(error) => Some(error),
+            AppError::InvalidUtf8(error) => Some(error),
+            _ => None,
         }
     }
 }
+
+impl From<io::Error> for AppError {
+    fn from(error: io::Error) -> Self {
+        AppError::Io(error)
+    }
+}
+
+impl From<Utf8Error> for AppError {
+    fn from(error: Utf8Error) -> Self {
+        AppError::InvalidUtf8(error)
+    }
+}
+
+/// Always produces [`AppError::Other`], even for categories that have a typed variant, as the
+/// message alone doesn't hold the fields of the variant
+impl From<(ErrorType, String)> for AppError {
+    fn from((error_type, message): (ErrorType, String)) -> Self {
+        AppError::new(error_type, message)
+    }
+}
+
+fn write_suggestion(f: &mut Formatter<'_>, suggestion: &Option<String>) -> std::fmt::Result {
+    match suggestion {
+        Some(suggestion) => write!(f, ", did you mean '{suggestion}'?"),
+        None => Ok(()),
+    }
+}
@@ -1,5 +1,7 @@
 use std::{
     borrow::Cow,
+    fs,
+    path::PathBuf,
     process::{Command, Output},
 };
 
@@ -21,19 +23,211 @@ pub(crate) struct Url {
 }
 
 #[derive(Default)]
-pub struct Git;
+pub struct Git {
+    /// Directory that every `git` process is run from, instead of the current working directory
+    directory: Option<PathBuf>,
+}
+
+/// Runs every `git` process from the current working directory, in place of the unit struct that
+/// `Git` used to be, e.g. `view_repository(GIT)`
+pub const GIT: Git = Git { directory: None };
+
+impl Git {
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Mirrors `git -C <directory>`
+    fn git(&self) -> Command {
+        let mut git = Command::new("git");
+        if let Some(directory) = &self.directory {
+            git.arg("-C").arg(directory);
+        }
+        git
+    }
+
+    fn command(&self, git_command: GitCommand) -> Result<Output, std::io::Error> {
+        match git_command {
+            GitCommand::ConfigList => self.git().arg("config").arg("--list").arg("-z").output(),
+            GitCommand::Head => self
+                .git()
+                .arg("rev-parse")
+                .arg("--is-bare-repository")
+                .arg("--show-prefix")
+                .arg("HEAD")
+                .arg("--symbolic-full-name")
+                .arg("HEAD")
+                .output(),
+            // Doesn't resolve HEAD, so that a repository without any commits yet is still detected
+            GitCommand::Repository => self
+                .git()
+                .arg("rev-parse")
+                .arg("--is-bare-repository")
+                .arg("--show-prefix")
+                .output(),
+            GitCommand::HeadName => self
+                .git()
+                .arg("symbolic-ref")
+                .arg("--quiet")
+                .arg("HEAD")
+                .output(),
+            GitCommand::Refs => self
+                .git()
+                .arg("for-each-ref")
+                .arg("--format=%(refname)%00%(objectname)%00%(*objectname)%00%(symref)")
+                .arg("refs/heads")
+                .arg("refs/remotes")
+                .arg("refs/tags")
+                .output(),
+            GitCommand::TopLevel => self.git().arg("rev-parse").arg("--show-toplevel").output(),
+            GitCommand::Submodules => self
+                .git()
+                .arg("config")
+                .arg("--blob")
+                .arg("HEAD:.gitmodules")
+                .arg("--list")
+                .arg("-z")
+                .output(),
+            // Only an initialised submodule has a '.git' of its own
+            GitCommand::SubmoduleBranch(directory) => self
+                .git()
+                .arg("-C")
+                .arg(directory)
+                .arg("--git-dir=.git")
+                .arg("symbolic-ref")
+                .arg("-q")
+                .arg("--short")
+                .arg("HEAD")
+                .output(),
+            GitCommand::TreeEntry(commit, path) => self
+                .git()
+                .arg("rev-parse")
+                .arg("--verify")
+                .arg("--quiet")
+                .arg(format!("{}:{}", commit, path))
+                .output(),
+            GitCommand::TreeListing(reference, path) => self
+                .git()
+                .arg("ls-tree")
+                .arg("--full-tree")
+                .arg("--end-of-options")
+                .arg(reference)
+                .arg("--")
+                .arg(path)
+                .output(),
+            GitCommand::TrackedPaths(reference) => self
+                .git()
+                .arg("ls-tree")
+                .arg("-r")
+                .arg("--full-tree")
+                .arg("--name-only")
+                .arg("--end-of-options")
+                .arg(reference)
+                .output(),
+            // `checkout` doesn't support `--end-of-options`, callers reject references that start
+            // with '-' and the trailing '--' stops the reference being taken as a path
+            GitCommand::Checkout(reference) => {
+                self.git().arg("checkout").arg(reference).arg("--").output()
+            }
+            GitCommand::Renames(commit, path) => self
+                .git()
+                .arg("log")
+                .arg("--follow")
+                .arg("--name-status")
+                .arg("--format=")
+                .arg(format!("{}..HEAD", commit))
+                .arg("--")
+                // The path is relative to the root, not the current working directory
+                .arg(format!(":(top,literal){}", path))
+                .output(),
+            GitCommand::RebaseHeadNames => self
+                .git()
+                .arg("rev-parse")
+                .arg("--git-path")
+                .arg("rebase-merge/head-name")
+                .arg("--git-path")
+                .arg("rebase-apply/head-name")
+                .output(),
+            GitCommand::Revision(revision) => self
+                .git()
+                .arg("rev-parse")
+                .arg("--verify")
+                .arg("--quiet")
+                .arg(format!("{}^{{commit}}", revision))
+                .output(),
+            GitCommand::SymbolicBranch(branch) => self
+                .git()
+                .arg("rev-parse")
+                .arg("--symbolic-full-name")
+                .arg(branch)
+                .output(),
+            GitCommand::RemoteBranchesContaining(remote, commit) => self
+                .git()
+                .arg("branch")
+                .arg("-r")
+                .arg("--contains")
+                .arg(commit)
+                .arg("--list")
+                .arg(format!("{}/*", remote))
+                .output(),
+            GitCommand::PushedBoundary(remote, commit) => self
+                .git()
+                .arg("rev-list")
+                .arg("--boundary")
+                .arg(commit)
+                .arg("--not")
+                .arg(format!("--remotes={}", remote))
+                .output(),
+            GitCommand::CommitMessages(base) => self
+                .git()
+                .arg("log")
+                .arg("--format=%B")
+                .arg(format!("{}..HEAD", base))
+                .output(),
+            GitCommand::CommitMessage(commit) => self
+                .git()
+                .arg("log")
+                .arg("-1")
+                .arg("--format=%B")
+                .arg(commit)
+                .output(),
+            // Messages span lines, so each one ends with a NUL
+            GitCommand::MergeMessages(commit, base) => self
+                .git()
+                .arg("log")
+                .arg("--ancestry-path")
+                .arg("--merges")
+                .arg("--reverse")
+                .arg("--format=%B%x00")
+                .arg(format!("{}..{}", commit, base))
+                .output(),
+        }
+    }
+}
 
 pub(crate) enum GitCommand<'a> {
-    IsValidRepository,
-    LocalBranch,
-    DefaultRemote,
-    TrackedRemote(&'a str),
-    UpstreamBranch(&'a str),
-    DefaultBranch(&'a str),
-    IsValidRemote(&'a str),
-    CurrentTag,
-    CurrentCommit,
-    CurrentWorkingDirectory,
+    ConfigList,
+    Head,
+    Repository,
+    HeadName,
+    Refs,
+    TopLevel,
+    Submodules,
+    SubmoduleBranch(&'a str),
+    TreeEntry(&'a str, &'a str),
+    TreeListing(&'a str, &'a str),
+    TrackedPaths(&'a str),
+    Renames(&'a str, &'a str),
+    Checkout(&'a str),
+    RebaseHeadNames,
+    Revision(&'a str),
+    SymbolicBranch(&'a str),
+    RemoteBranchesContaining(&'a str, &'a str),
+    PushedBoundary(&'a str, &'a str),
+    CommitMessages(&'a str),
+    CommitMessage(&'a str),
+    MergeMessages(&'a str, &'a str),
 }
 
 pub enum GitOutput {
@@ -43,104 +237,143 @@ pub enum GitOutput {
 
 #[cfg_attr(test, automock)]
 pub trait GitTrait {
-    fn is_valid_repository(&self) -> Result<GitOutput, AppError>;
-    fn get_local_branch(&self) -> Result<GitOutput, AppError>;
-    fn get_default_remote(&self) -> Result<GitOutput, AppError>;
-    fn get_tracked_remote(&self, tracked: &str) -> Result<GitOutput, AppError>;
-    fn get_upstream_branch(&self, branch: &str) -> Result<GitOutput, AppError>;
-    fn get_default_branch(&self, remote: &str) -> Result<GitOutput, AppError>;
-    fn is_valid_remote(&self, remote: &str) -> Result<GitOutput, AppError>;
-    fn get_current_tag(&self) -> Result<GitOutput, AppError>;
-    fn get_current_commit(&self) -> Result<GitOutput, AppError>;
-    fn get_current_working_directory(&self) -> Result<GitOutput, AppError>;
+    fn get_config_list(&self) -> Result<GitOutput, AppError>;
+    fn get_head(&self) -> Result<GitOutput, AppError>;
+    fn get_refs(&self) -> Result<GitOutput, AppError>;
+    fn get_top_level(&self) -> Result<GitOutput, AppError>;
+    fn get_submodules(&self) -> Result<GitOutput, AppError>;
+    fn get_submodule_branch(&self, directory: &str) -> Result<GitOutput, AppError>;
+    fn get_tree_entry(&self, commit: &str, path: &str) -> Result<GitOutput, AppError>;
+    fn get_tree_listing(&self, reference: &str, path: &str) -> Result<GitOutput, AppError>;
+    fn get_tracked_paths(&self, reference: &str) -> Result<GitOutput, AppError>;
+    fn get_renames(&self, commit: &str, path: &str) -> Result<GitOutput, AppError>;
+    fn checkout(&self, reference: &str) -> Result<GitOutput, AppError>;
+    fn get_rebase_head_name(&self) -> Result<GitOutput, AppError>;
+    fn get_revision(&self, revision: &str) -> Result<GitOutput, AppError>;
+    fn get_symbolic_branch(&self, branch: &str) -> Result<GitOutput, AppError>;
+    fn get_remote_branches_containing(
+        &self,
+        remote: &str,
+        commit: &str,
+    ) -> Result<GitOutput, AppError>;
+    fn get_pushed_boundary(&self, remote: &str, commit: &str) -> Result<GitOutput, AppError>;
+    fn get_commit_messages(&self, base: &str) -> Result<GitOutput, AppError>;
+    fn get_commit_message(&self, commit: &str) -> Result<GitOutput, AppError>;
+    fn get_merge_messages(&self, commit: &str, base: &str) -> Result<GitOutput, AppError>;
 }
 
 impl GitTrait for Git {
-    fn is_valid_repository(&self) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::IsValidRepository)?)
+    fn get_config_list(&self) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::ConfigList)?)
+    }
+
+    fn get_head(&self) -> Result<GitOutput, AppError> {
+        if let GitOutput::Ok(head) = execute(self.command(GitCommand::Head)?)? {
+            return Ok(GitOutput::Ok(head));
+        }
+
+        // HEAD can't be resolved when it's unborn, so the repository is detected without it
+        let repository = match execute(self.command(GitCommand::Repository)?)? {
+            GitOutput::Ok(repository) => repository,
+            GitOutput::Err(err) => return Ok(GitOutput::Err(err)),
+        };
+        let mut repository = repository.lines();
+        let is_bare = repository.next().unwrap_or_default();
+        let prefix = repository.next().unwrap_or_default();
+        let full_name = match execute(self.command(GitCommand::HeadName)?)? {
+            GitOutput::Ok(full_name) => full_name,
+            GitOutput::Err(_) => "HEAD".into(),
+        };
+
+        Ok(GitOutput::Ok(format!("{is_bare}\n{prefix}\n\n{full_name}")))
     }
 
-    fn get_local_branch(&self) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::LocalBranch)?)
+    fn get_refs(&self) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::Refs)?)
     }
 
-    fn get_default_remote(&self) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::DefaultRemote)?)
+    fn get_top_level(&self) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::TopLevel)?)
     }
 
-    fn get_tracked_remote(&self, tracked: &str) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::TrackedRemote(tracked))?)
+    fn get_submodules(&self) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::Submodules)?)
     }
 
-    fn get_upstream_branch(&self, branch: &str) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::UpstreamBranch(branch))?)
+    fn get_submodule_branch(&self, directory: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::SubmoduleBranch(directory))?)
     }
 
-    fn get_default_branch(&self, remote: &str) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::DefaultBranch(remote))?)
+    fn get_tree_entry(&self, commit: &str, path: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::TreeEntry(commit, path))?)
     }
 
-    fn is_valid_remote(&self, remote: &str) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::IsValidRemote(remote))?)
+    fn get_tree_listing(&self, reference: &str, path: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::TreeListing(reference, path))?)
     }
 
-    fn get_current_tag(&self) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::CurrentTag)?)
+    fn get_tracked_paths(&self, reference: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::TrackedPaths(reference))?)
     }
 
-    fn get_current_commit(&self) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::CurrentCommit)?)
+    fn get_renames(&self, commit: &str, path: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::Renames(commit, path))?)
     }
 
-    fn get_current_working_directory(&self) -> Result<GitOutput, AppError> {
-        execute(command(GitCommand::CurrentWorkingDirectory)?)
+    fn checkout(&self, reference: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::Checkout(reference))?)
+    }
+
+    fn get_rebase_head_name(&self) -> Result<GitOutput, AppError> {
+        // The paths are relative to the directory that git was run from
+        let paths = match execute(self.command(GitCommand::RebaseHeadNames)?)? {
+            GitOutput::Ok(paths) => paths,
+            GitOutput::Err(err) => return Ok(GitOutput::Err(err)),
+        };
+
+        for path in paths.lines() {
+            let path = match &self.directory {
+                Some(directory) => directory.join(path),
+                None => PathBuf::from(path),
+            };
+            if let Ok(head_name) = fs::read_to_string(path) {
+                return Ok(GitOutput::Ok(head_name.trim().into()));
+            }
+        }
+
+        Ok(GitOutput::Err("No rebase in progress".into()))
+    }
+
+    fn get_revision(&self, revision: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::Revision(revision))?)
+    }
+
+    fn get_symbolic_branch(&self, branch: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::SymbolicBranch(branch))?)
+    }
+
+    fn get_remote_branches_containing(
+        &self,
+        remote: &str,
+        commit: &str,
+    ) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::RemoteBranchesContaining(remote, commit))?)
+    }
+
+    fn get_pushed_boundary(&self, remote: &str, commit: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::PushedBoundary(remote, commit))?)
+    }
+
+    fn get_commit_messages(&self, base: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::CommitMessages(base))?)
+    }
+
+    fn get_commit_message(&self, commit: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::CommitMessage(commit))?)
     }
-}
 
-fn command(git_command: GitCommand) -> Result<Output, std::io::Error> {
-    match git_command {
-        GitCommand::IsValidRepository => Command::new("git")
-            .arg("rev-parse")
-            .arg("--is-inside-work-tree")
-            .output(),
-        GitCommand::LocalBranch => Command::new("git")
-            .arg("symbolic-ref")
-            .arg("-q")
-            .arg("--short")
-            .arg("HEAD")
-            .output(),
-        GitCommand::DefaultRemote => Command::new("git")
-            .arg("config")
-            .arg("open.default.remote")
-            .output(),
-        GitCommand::TrackedRemote(branch) => Command::new("git")
-            .arg("config")
-            .arg(format!("branch.{}.remote", branch))
-            .output(),
-        GitCommand::UpstreamBranch(branch) => Command::new("git")
-            .arg("config")
-            .arg(format!("branch.{}.merge", branch))
-            .output(),
-        GitCommand::DefaultBranch(remote) => Command::new("git")
-            .arg("rev-parse")
-            .arg("--abbrev-ref")
-            .arg(format!("{}/HEAD", remote))
-            .output(),
-        GitCommand::IsValidRemote(remote) => Command::new("git")
-            .arg("ls-remote")
-            .arg("--get-url")
-            .arg(remote)
-            .output(),
-        GitCommand::CurrentTag => Command::new("git")
-            .arg("describe")
-            .arg("--tags")
-            .arg("--exact-match")
-            .output(),
-        GitCommand::CurrentCommit => Command::new("git").arg("rev-parse").arg("HEAD").output(),
-        GitCommand::CurrentWorkingDirectory => Command::new("git")
-            .arg("rev-parse")
-            .arg("--show-prefix")
-            .output(),
+    fn get_merge_messages(&self, commit: &str, base: &str) -> Result<GitOutput, AppError> {
+        execute(self.command(GitCommand::MergeMessages(commit, base))?)
     }
 }
 
@@ -1,12 +1,66 @@
 mod error;
 mod git;
+#[cfg(feature = "libgit2")]
+mod libgit;
+mod snapshot;
+
+use std::{
+    borrow::Cow,
+    env,
+    fmt::{Display, Formatter},
+    io,
+    path::{Path, PathBuf},
+};
+
+use git::{Local, Url};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use regex::Regex;
+use snapshot::RepoSnapshot;
+
+pub use error::{AppError, ErrorType};
+pub use git::{Git, GitOutput, GitTrait, GIT};
+#[cfg(feature = "libgit2")]
+pub use libgit::LibGit;
+
+#[derive(Debug, PartialEq)]
+enum RemoteKind {
+    Push,
+    Fetch,
+}
 
-use std::borrow::Cow;
+/// What to do when the commit being viewed hasn't been pushed to the remote
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Unpushed {
+    /// Open the commit anyway after printing a warning
+    #[default]
+    Warn,
+    /// Open the newest ancestor of the commit that has been pushed
+    Ancestor,
+    /// Error out instead of opening the commit
+    Refuse,
+}
 
-use error::{AppError, ErrorType};
-use git::{GitOutput, GitTrait, Local, Url};
+/// What to do when the path being viewed doesn't exist on the remote
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum MissingPath {
+    /// Error out instead of opening the path
+    #[default]
+    Refuse,
+    /// Open the nearest parent directory that exists on the remote
+    Parent,
+}
 
-pub use git::Git;
+/// What to do with the ref of a url being resolved to the local checkout
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ResolveRef {
+    /// Resolve the path without looking at the ref
+    #[default]
+    Ignore,
+    /// Error out if the ref, or the path within it, can't be found locally
+    Verify,
+    /// Check out the ref
+    Checkout,
+}
 
 #[derive(Default)]
 pub struct GitView<'a> {
@@ -15,10 +69,163 @@ pub struct GitView<'a> {
     commit: Option<&'a str>,
     issue: Option<&'a str>,
     path: Option<&'a str>,
+    resolve: Option<&'a str>,
+    resolve_ref: ResolveRef,
+    unpushed: Unpushed,
+    missing_path: MissingPath,
+    is_origin_pr: bool,
+    is_print: bool,
+    is_all_remotes: bool,
+    is_upstream: bool,
+    is_verbose: bool,
+    is_interactive: bool,
+}
+
+#[derive(Default)]
+pub struct GitViewBuilder<'a> {
+    remote: Option<&'a str>,
+    branch: Option<&'a str>,
+    commit: Option<&'a str>,
+    issue: Option<&'a str>,
+    path: Option<&'a str>,
+    resolve: Option<&'a str>,
+    resolve_ref: ResolveRef,
+    unpushed: Unpushed,
+    missing_path: MissingPath,
+    is_origin_pr: bool,
     is_print: bool,
+    is_all_remotes: bool,
+    is_upstream: bool,
+    is_verbose: bool,
+    is_interactive: bool,
+}
+
+impl<'a> GitViewBuilder<'a> {
+    pub fn with_remote(mut self, remote: impl Into<Option<&'a str>>) -> Self {
+        self.remote = remote.into();
+        self
+    }
+
+    pub fn with_branch(mut self, branch: impl Into<Option<&'a str>>) -> Self {
+        self.branch = branch.into();
+        self
+    }
+
+    pub fn with_commit(mut self, commit: impl Into<Option<&'a str>>) -> Self {
+        self.commit = commit.into();
+        self
+    }
+
+    pub fn with_issue(mut self, issue: impl Into<Option<&'a str>>) -> Self {
+        self.issue = issue.into();
+        self
+    }
+
+    pub fn with_path(mut self, path: impl Into<Option<&'a str>>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    pub fn with_resolve(mut self, resolve: impl Into<Option<&'a str>>) -> Self {
+        self.resolve = resolve.into();
+        self
+    }
+
+    pub fn with_resolve_ref(mut self, resolve_ref: ResolveRef) -> Self {
+        self.resolve_ref = resolve_ref;
+        self
+    }
+
+    pub fn with_unpushed(mut self, unpushed: Unpushed) -> Self {
+        self.unpushed = unpushed;
+        self
+    }
+
+    pub fn with_missing_path(mut self, missing_path: MissingPath) -> Self {
+        self.missing_path = missing_path;
+        self
+    }
+
+    pub fn with_origin_pr(mut self, is_origin_pr: bool) -> Self {
+        self.is_origin_pr = is_origin_pr;
+        self
+    }
+
+    pub fn with_print(mut self, is_print: bool) -> Self {
+        self.is_print = is_print;
+        self
+    }
+
+    pub fn with_all_remotes(mut self, is_all_remotes: bool) -> Self {
+        self.is_all_remotes = is_all_remotes;
+        self
+    }
+
+    pub fn with_upstream(mut self, is_upstream: bool) -> Self {
+        self.is_upstream = is_upstream;
+        self
+    }
+
+    pub fn with_verbose(mut self, is_verbose: bool) -> Self {
+        self.is_verbose = is_verbose;
+        self
+    }
+
+    /// Allows prompting on stdin when a choice can't be made from the config
+    pub fn with_interactive(mut self, is_interactive: bool) -> Self {
+        self.is_interactive = is_interactive;
+        self
+    }
+
+    pub fn build(self) -> GitView<'a> {
+        GitView {
+            remote: self.remote,
+            branch: self.branch,
+            commit: self.commit,
+            issue: self.issue,
+            path: self.path,
+            resolve: self.resolve,
+            resolve_ref: self.resolve_ref,
+            unpushed: self.unpushed,
+            missing_path: self.missing_path,
+            is_origin_pr: self.is_origin_pr,
+            is_print: self.is_print,
+            is_all_remotes: self.is_all_remotes,
+            is_upstream: self.is_upstream,
+            is_verbose: self.is_verbose,
+            is_interactive: self.is_interactive,
+        }
+    }
 }
 
 impl<'a> GitView<'a> {
+    pub fn builder() -> GitViewBuilder<'a> {
+        GitViewBuilder::default()
+    }
+
+    /// Checkouts listed in `view.repositories`, which a url to `--resolve` is looked for in when
+    /// the current repository has no remote for it, with a leading '~/' expanded to the home
+    /// directory
+    pub fn known_repositories(git: &impl GitTrait) -> Result<Vec<PathBuf>, AppError> {
+        let config = match git.get_config_list()? {
+            GitOutput::Ok(config) => config,
+            GitOutput::Err(err) => return Err(AppError::new(ErrorType::CommandFailed, err)),
+        };
+
+        Ok(config
+            .split('\0')
+            .filter_map(|entry| entry.split_once('\n'))
+            .filter(|(key, _)| *key == "view.repositories")
+            .map(
+                |(_, repository)| match (repository.strip_prefix("~/"), env::var_os("HOME")) {
+                    (Some(repository), Some(home)) => PathBuf::from(home).join(repository),
+                    _ => PathBuf::from(repository),
+                },
+            )
+            .collect())
+    }
+
+    #[deprecated(note = "use `GitView::builder` instead, which covers every option")]
     pub fn new(
         branch: Option<&'a str>,
         remote: Option<&'a str>,
@@ -27,84 +234,404 @@ impl<'a> GitView<'a> {
         path: Option<&'a str>,
         is_print: bool,
     ) -> Self {
-        Self {
-            remote,
-            branch,
-            commit,
-            issue,
-            path,
-            is_print,
-        }
+        Self::builder()
+            .with_branch(branch)
+            .with_remote(remote)
+            .with_commit(commit)
+            .with_issue(issue)
+            .with_path(path)
+            .with_print(is_print)
+            .build()
     }
 
     pub fn view_repository(&self, git: impl GitTrait) -> Result<(), AppError> {
-        self.is_valid_repository(&git)?;
-        let local_ref = self.get_local_ref(&git)?;
-        let remote = self.populate_remote(&local_ref, &git)?;
-        let remote_ref = self.get_remote_reference(&local_ref, &remote, &git)?;
+        let snapshot = RepoSnapshot::new(&git)?;
 
-        // Retrieve the full git_url
-        // e.g https://github.com/sgoudham/git-view.git
-        let git_url = self.get_git_url(&remote, &git)?;
-        let url = self.parse_git_url(&git_url)?;
-        let final_url = self.generate_final_url(&remote_ref, &url, &git)?;
+        if let Some(web_url) = self.resolve {
+            println!("{}", self.resolve_web_url(web_url, &snapshot, &git)?);
+            return Ok(());
+        }
+
+        let local_ref = self.get_local_ref(&snapshot, &git)?;
 
-        if self.is_print {
-            println!("{}", final_url);
+        let final_urls = if self.is_all_remotes {
+            self.generate_all_remote_urls(&local_ref, &snapshot, &git)?
         } else {
-            webbrowser::open(final_url.as_str())?;
+            let remote = self.populate_remote(&local_ref, &snapshot)?;
+            vec![self.generate_url(&local_ref, &remote, &snapshot, &git)?]
+        };
+
+        for final_url in final_urls {
+            if self.is_print {
+                println!("{}", final_url);
+            } else {
+                webbrowser::open(final_url.as_str()).map_err(|source| {
+                    AppError::BrowserLaunchFailed {
+                        url: final_url.clone(),
+                        source,
+                    }
+                })?;
+            }
         }
 
         Ok(())
     }
 
-    fn is_valid_repository(&self, git: &impl GitTrait) -> Result<(), AppError> {
-        match git.is_valid_repository()? {
-            GitOutput::Ok(_) => Ok(()),
-            GitOutput::Err(_) => Err(AppError::new(
-                ErrorType::MissingGitRepository,
-                "Looks like you're not in a valid git repository!".to_string(),
-            )),
+    /// Resolves the url to open on every remote, skipping the remotes that can't be viewed
+    fn generate_all_remote_urls(
+        &self,
+        local_ref: &Local,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<Vec<String>, AppError> {
+        let mut final_urls = Vec::new();
+        for remote in snapshot.remotes() {
+            match self.generate_url(local_ref, remote, snapshot, git) {
+                Ok(final_url) => final_urls.push(final_url),
+                // e.g. a local mirror that isn't hosted on a forge, or a remote that was never fetched
+                Err(err)
+                    if matches!(
+                        err.error_type(),
+                        ErrorType::InvalidGitUrl
+                            | ErrorType::MissingGitRemote
+                            | ErrorType::MissingDefaultBranch
+                    ) =>
+                {
+                    eprintln!("Skipping remote '{remote}': {err}")
+                }
+                // Errors that aren't specific to the remote would fail on every remote
+                Err(err) => return Err(err),
+            }
+        }
+
+        if final_urls.is_empty() {
+            return Err(AppError::MissingGitRemote {
+                remote: None,
+                suggestion: None,
+                message: "Looks like none of your git remotes can be viewed".to_string(),
+            });
+        }
+        Ok(final_urls)
+    }
+
+    /// Resolves the url to open on the remote
+    fn generate_url(
+        &self,
+        local_ref: &Local,
+        remote: &str,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<String, AppError> {
+        // Retrieve the full git_url
+        // e.g https://github.com/sgoudham/git-view.git
+        let git_url = self.get_git_url(remote, snapshot)?;
+
+        match self.get_submodule(&git_url, snapshot, git)? {
+            // Paths within a submodule live in the submodule's own repository
+            Some(submodule) => self.generate_submodule_url(&submodule),
+            None => {
+                let url = self.parse_git_url(&git_url)?;
+                // Issues are named after the local branch, which may not have been pushed or may
+                // track a branch of another name, e.g. 'PROJ-1234-thing' tracking 'origin/main'
+                if let (Some(issue), Local::Branch(branch)) = (self.issue, local_ref) {
+                    return self.handle_issue_flag(issue, branch, remote, &url, snapshot, git);
+                }
+                let remote_ref = self.get_remote_reference(local_ref, remote, snapshot, git)?;
+                self.generate_final_url(&remote_ref, remote, &url, snapshot, git)
+            }
         }
     }
 
-    fn get_local_ref(&self, git: &impl GitTrait) -> Result<Local, AppError> {
-        if self.branch.is_none() {
-            match git.get_local_branch()? {
-                GitOutput::Ok(output) => Ok(Local::Branch(Cow::Owned(output))),
-                GitOutput::Err(_) => Ok(Local::NotBranch),
+    /// Maps the url of a file or directory on the forge back to the local checkout, the inverse of
+    /// [`GitView::generate_final_url`]
+    /// e.g. `https://github.com/sgoudham/git-view/blob/main/src/lib.rs#L10-L20` -> `/home/me/git-view/src/lib.rs:10`
+    fn resolve_web_url(
+        &self,
+        web_url: &str,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<String, AppError> {
+        let invalid_url = || AppError::InvalidGitUrl {
+            url: web_url.into(),
+            message: format!("Sorry, couldn't parse url '{web_url}'"),
+        };
+        let parsed_url = url::Url::parse(web_url).map_err(|_| invalid_url())?;
+        let host = parsed_url.host_str().ok_or_else(invalid_url)?;
+        let web_path = parsed_url.path().trim_matches('/');
+
+        // Find the remote that the repository of the url belongs to, e.g. 'sgoudham/git-view'
+        let (remote, view) = snapshot
+            .remotes()
+            .into_iter()
+            .find_map(|remote| {
+                let url = self.parse_git_url(&snapshot.get_remote_url(remote)?).ok()?;
+                let view = web_path
+                    .get(..url.path.len())
+                    .filter(|path| {
+                        url.domain.eq_ignore_ascii_case(host)
+                            && path.eq_ignore_ascii_case(&url.path)
+                    })
+                    .and_then(|_| web_path[url.path.len()..].strip_prefix('/'))?;
+                Some((remote, view))
+            })
+            .ok_or_else(|| AppError::MissingGitRemote {
+                remote: None,
+                suggestion: None,
+                message: format!(
+                    "Looks like none of your git remotes point at '{host}/{web_path}'"
+                ),
+            })?;
+
+        // GitLab prefixes the views of a repository with '-/'
+        let view = view.strip_prefix("-/").unwrap_or(view);
+        let segments = match view.split_once('/') {
+            Some(("tree" | "blob", segments)) => segments
+                .split('/')
+                .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+                .collect::<Vec<_>>(),
+            _ => {
+                return Err(AppError::InvalidGitUrl {
+                    url: web_url.into(),
+                    message: format!("Url '{web_url}' doesn't point at a file or directory"),
+                })
+            }
+        };
+
+        // Refs can contain slashes, so the ref is the longest leading segments that name a known
+        // branch or tag, otherwise it's the first segment, e.g. a commit
+        let split = (1..=segments.len())
+            .rev()
+            .find(|split| {
+                let reference = segments[..*split].join("/");
+                snapshot.has_remote_tracking_branch(remote, &reference)
+                    || snapshot.has_tag(&reference)
+            })
+            .unwrap_or(1);
+        let reference = segments[..split].join("/");
+        let path = segments[split..].join("/");
+
+        // The ref is passed to git, so it must not be mistaken for an option, e.g. '--force'
+        if self.resolve_ref != ResolveRef::Ignore && reference.starts_with('-') {
+            return Err(AppError::new(
+                ErrorType::InvalidRevision,
+                format!("Ref '{reference}' in url '{web_url}' is not a valid ref"),
+            ));
+        }
+
+        let local_reference = snapshot.local_reference(remote, &reference);
+        match self.resolve_ref {
+            ResolveRef::Ignore => {}
+            ResolveRef::Verify => {
+                // The root of the ref has nothing to list, and git rejects an empty pathspec
+                let output = if path.is_empty() {
+                    git.get_revision(&local_reference)?
+                } else {
+                    git.get_tree_listing(&local_reference, &path)?
+                };
+                match output {
+                    GitOutput::Ok(listing) if path.is_empty() || !listing.is_empty() => {}
+                    GitOutput::Ok(_) => {
+                        return Err(AppError::new(
+                            ErrorType::MissingPath,
+                            format!("Path '{path}' does not exist at '{reference}'"),
+                        ))
+                    }
+                    GitOutput::Err(_) => {
+                        return Err(AppError::new(
+                            ErrorType::InvalidRevision,
+                            format!(
+                                "Could not find '{reference}' locally, try fetching '{remote}'"
+                            ),
+                        ))
+                    }
+                }
+            }
+            ResolveRef::Checkout => {
+                if let GitOutput::Err(_) = git.get_revision(&local_reference)? {
+                    return Err(AppError::new(
+                        ErrorType::InvalidRevision,
+                        format!("Could not find '{reference}' locally, try fetching '{remote}'"),
+                    ));
+                }
+                if let GitOutput::Err(err) = git.checkout(&reference)? {
+                    return Err(AppError::new(ErrorType::CommandFailed, err));
+                }
+            }
+        }
+
+        let top_level = match git.get_top_level()? {
+            GitOutput::Ok(top_level) => top_level,
+            GitOutput::Err(_) => {
+                return Err(AppError::new(
+                    ErrorType::BareRepository,
+                    format!("Cannot resolve '{web_url}' in a bare repository as it has no working directory"),
+                ))
             }
+        };
+        let local_path = if path.is_empty() {
+            top_level
         } else {
-            Ok(Local::Branch(Cow::Borrowed(self.branch.as_ref().unwrap())))
+            format!("{top_level}/{path}")
+        };
+
+        // e.g. 'L10', 'L10-L20' on GitHub or 'L10-20' on GitLab
+        let line = parsed_url
+            .fragment()
+            .and_then(|fragment| fragment.strip_prefix('L'))
+            .and_then(|lines| lines.split('-').next())
+            .and_then(|line| line.parse::<usize>().ok());
+
+        Ok(match line {
+            Some(line) => format!("{local_path}:{line}"),
+            None => local_path,
+        })
+    }
+
+    fn get_local_ref(
+        &self,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<Local<'_>, AppError> {
+        match self.branch {
+            // Resolve branch expressions, e.g. '@{-1}', '@{upstream}', to the branch name
+            Some(branch) if branch.starts_with('@') => match git.get_symbolic_branch(branch)? {
+                GitOutput::Ok(full_name) => {
+                    match full_name.strip_prefix("refs/heads/").or_else(|| {
+                        full_name
+                            .strip_prefix("refs/remotes/")
+                            .and_then(|remote_branch| remote_branch.split_once('/'))
+                            .map(|(_, branch)| branch)
+                    }) {
+                        Some(branch) => Ok(Local::Branch(Cow::Owned(branch.into()))),
+                        None => Err(AppError::new(
+                            ErrorType::InvalidRevision,
+                            format!("Could not resolve '{branch}' to a branch"),
+                        )),
+                    }
+                }
+                GitOutput::Err(err) => Err(AppError::new(ErrorType::InvalidRevision, err)),
+            },
+            Some(branch) => Ok(Local::Branch(Cow::Borrowed(branch))),
+            None => match snapshot.current_branch() {
+                Some(branch) => Ok(Local::Branch(Cow::Owned(branch.into()))),
+                // A rebase detaches HEAD, but the branch being rebased is still the one being viewed
+                None => match git.get_rebase_head_name()? {
+                    GitOutput::Ok(head_name) => match head_name.strip_prefix("refs/heads/") {
+                        Some(branch) => {
+                            self.print_detached_source(format!("branch '{branch}' being rebased"));
+                            Ok(Local::Branch(Cow::Owned(branch.into())))
+                        }
+                        None => Ok(Local::NotBranch),
+                    },
+                    GitOutput::Err(_) => Ok(Local::NotBranch),
+                },
+            },
         }
     }
 
     /// Populates the remote variable within [`GitView`]
-    /// User Given Remote -> Default Remote in Config -> Tracked Remote -> 'origin'
+    /// User Given Remote -> Default Remote in Config -> Push Remote -> Tracked Remote -> 'origin'
+    /// -> Only Remote -> Remote Priority in Config -> Interactive Prompt
+    ///
+    /// The push remote is only considered for views of the branch itself, issues and pull
+    /// requests live in the repository that the branch is fetched from
     fn populate_remote(
         &self,
         local: &Local,
-        git: &impl GitTrait,
+        snapshot: &RepoSnapshot,
     ) -> Result<Cow<'_, str>, AppError> {
+        // Forks view the repository they were forked from instead
+        if self.is_upstream {
+            return self.get_upstream_remote(snapshot);
+        }
+
         // Priority goes to user given remote
-        if self.remote.is_none() {
-            match local {
+        match self.remote {
+            Some(remote) => Ok(Cow::Borrowed(remote)),
+            None => match local {
                 Local::Branch(branch) => {
                     // Priority then goes to the default remote
-                    match git.get_default_remote()? {
-                        GitOutput::Ok(def) => Ok(Cow::Owned(def)),
-                        // Priority then goes to the tracked remote
-                        GitOutput::Err(_) => match git.get_tracked_remote(branch)? {
-                            GitOutput::Ok(tracked) => Ok(Cow::Owned(tracked)),
-                            // Default to the 'origin' remote
-                            GitOutput::Err(_) => Ok(Cow::Owned("origin".into())),
-                        },
+                    if let Some(def) = snapshot.config("open.default.remote") {
+                        return Ok(Cow::Owned(def.into()));
+                    }
+
+                    // Priority then goes to the push remote of the branch, then of the repository
+                    if self.remote_kind() == RemoteKind::Push {
+                        if let Some(push) = snapshot
+                            .config(&format!("branch.{}.pushRemote", branch))
+                            .or_else(|| snapshot.config("remote.pushDefault"))
+                        {
+                            return Ok(Cow::Owned(push.into()));
+                        }
+                    }
+
+                    // Priority then goes to the tracked remote
+                    match snapshot.config(&format!("branch.{}.remote", branch)) {
+                        Some(tracked) => Ok(Cow::Owned(tracked.into())),
+                        None => self.select_remote(snapshot),
                     }
                 }
-                Local::NotBranch => Ok(Cow::Owned("origin".into())),
-            }
+                Local::NotBranch => self.select_remote(snapshot),
+            },
+        }
+    }
+
+    /// Upstream Remote in Config -> 'upstream'
+    fn get_upstream_remote(&self, snapshot: &RepoSnapshot) -> Result<Cow<'_, str>, AppError> {
+        match snapshot.config("view.upstreamRemote") {
+            Some(upstream) => Ok(Cow::Owned(upstream.into())),
+            None if snapshot.remotes().contains(&"upstream") => Ok(Cow::Owned("upstream".into())),
+            None => Err(AppError::MissingGitRemote {
+                remote: Some("upstream".into()),
+                suggestion: None,
+                message:
+                    "Looks like there's no 'upstream' remote, add one or set 'view.upstreamRemote'"
+                        .to_string(),
+            }),
+        }
+    }
+
+    /// Picks a remote when none is configured for the branch
+    /// 'origin' -> Only Remote -> Remote Priority in Config -> Interactive Prompt
+    fn select_remote(&self, snapshot: &RepoSnapshot) -> Result<Cow<'_, str>, AppError> {
+        let remotes = snapshot.remotes();
+
+        // Default to the 'origin' remote, which also reports a missing remote if there are none
+        if remotes.is_empty() || remotes.contains(&"origin") {
+            return Ok(Cow::Owned("origin".into()));
+        }
+        if let [remote] = remotes.as_slice() {
+            return Ok(Cow::Owned(remote.to_string()));
+        }
+
+        // e.g. 'view.remotePriority = github, gitlab'
+        if let Some(priority) = snapshot.config("view.remotePriority").and_then(|priority| {
+            priority
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .find(|remote| remotes.contains(remote))
+        }) {
+            return Ok(Cow::Owned(priority.into()));
+        }
+
+        if self.is_interactive {
+            return Ok(Cow::Owned(prompt_remote(&remotes)?));
+        }
+
+        Err(AppError::MissingGitRemote {
+            remote: None,
+            suggestion: None,
+            message: format!(
+                "Looks like there's no 'origin' remote, choose one of '{}' with --remote or set 'view.remotePriority'",
+                remotes.join("', '")
+            ),
+        })
+    }
+
+    fn remote_kind(&self) -> RemoteKind {
+        if self.issue.is_some() || self.is_origin_pr {
+            RemoteKind::Fetch
         } else {
-            Ok(Cow::Borrowed(self.remote.as_ref().unwrap()))
+            RemoteKind::Push
         }
     }
 
@@ -112,57 +639,253 @@ impl<'a> GitView<'a> {
         &self,
         local: &'a Local,
         remote: &'a str,
+        snapshot: &RepoSnapshot,
         git: &impl GitTrait,
     ) -> Result<Cow<'a, str>, AppError> {
         match local {
+            // The branches of a bare clone or mirror are the branches of the remote, and its HEAD
+            // is the default branch at the time of cloning
+            Local::Branch(branch) if snapshot.is_bare() => Ok(Cow::Borrowed(branch)),
             Local::Branch(branch) => {
-                match git.get_upstream_branch(branch)? {
-                    GitOutput::Ok(output) => Ok(Cow::Owned(
-                        output.trim_start_matches("refs/heads/").to_string(),
-                    )),
-                    // Upstream branch doesn't exist, try to retrieve default remote branch
-                    GitOutput::Err(_) => match git.get_default_branch(remote)? {
-                        GitOutput::Ok(default_branch) => {
-                            println!("Cannot verify '{remote}/{branch}' exists, defaulting to '{default_branch}'");
-                            return match default_branch.split_once('/') {
-                                Some((_, split_branch)) => Ok(Cow::Owned(split_branch.into())),
-                                None => Ok(Cow::Borrowed(branch)),
-                            };
+                // The upstream branch only exists on the tracked remote, a branch pushed
+                // elsewhere (e.g. a fork) keeps its local name
+                let is_tracked_remote = match snapshot.config(&format!("branch.{}.remote", branch))
+                {
+                    Some(tracked) => tracked == remote,
+                    None => true,
+                };
+
+                // Priority goes to the upstream branch if it still exists on the remote
+                if is_tracked_remote {
+                    if let Some(upstream) = snapshot.config(&format!("branch.{}.merge", branch)) {
+                        let upstream = upstream.trim_start_matches("refs/heads/");
+                        if snapshot.has_remote_tracking_branch(remote, upstream) {
+                            return Ok(Cow::Owned(upstream.into()));
                         }
-                        GitOutput::Err(_) => Err(AppError::new(
-                            ErrorType::MissingDefaultBranch,
-                            format!("Could not verify '{remote}/{branch}' exists and could not retrieve default branch")
-                        )),
-                    },
+                        eprintln!("Upstream branch '{remote}/{upstream}' of '{branch}' does not exist, trying '{remote}/{branch}'");
+                    }
+                }
+
+                // Priority then goes to the remote branch of the same name, e.g. 'push.default=current'
+                if snapshot.has_remote_tracking_branch(remote, branch) {
+                    return Ok(Cow::Borrowed(branch));
+                }
+
+                // Remote branch doesn't exist, try to retrieve default remote branch
+                // A branch given with '--branch' may be a typo of an existing one
+                let suggestion = self
+                    .branch
+                    .and_then(|_| suggest(branch, snapshot.branches(remote)));
+                match snapshot.find_default_branch(remote) {
+                    Some(default_branch) => {
+                        match suggestion {
+                            Some(suggestion) => eprintln!("Cannot find '{remote}/{branch}' in remote-tracking branches, did you mean '{suggestion}'? Defaulting to '{remote}/{default_branch}'\nView it with '--branch {suggestion}'"),
+                            // Branches of a fork aren't pushed to the parent repository
+                            None if self.is_upstream => eprintln!("Cannot find '{remote}/{branch}' in remote-tracking branches, defaulting to '{remote}/{default_branch}'"),
+                            None => eprintln!("Cannot find '{remote}/{branch}' in remote-tracking branches, defaulting to '{remote}/{default_branch}'\nPush it with 'git push --set-upstream {remote} {branch}' or track a remote branch of another name with 'git config branch.{branch}.merge refs/heads/<branch>'"),
+                        }
+                        Ok(Cow::Owned(default_branch.into()))
+                    }
+                    None => Err(AppError::MissingDefaultBranch {
+                        remote: remote.into(),
+                        branch: branch.to_string(),
+                        suggestion: suggestion.map(String::from),
+                    }),
                 }
             }
-            // Priority is given to the current tag
-            Local::NotBranch => match git.get_current_tag()? {
-                GitOutput::Ok(tag) => Ok(Cow::Owned(tag)),
-                // Priority is then given the current commit
-                GitOutput::Err(_) => match git.get_current_commit()? {
-                    GitOutput::Ok(commit_hash) => Ok(Cow::Owned(commit_hash)),
+            Local::NotBranch => {
+                // Priority is given to the current tag
+                if let Some(tag) = snapshot.current_tag() {
+                    self.print_detached_source(format!("tag '{tag}'"));
+                    return Ok(Cow::Owned(tag.into()));
+                }
+
+                let commit_hash = match snapshot.current_commit() {
+                    Some(commit_hash) => commit_hash,
                     // Error out if even the current commit could not be found
-                    GitOutput::Err(err) => Err(AppError::new(ErrorType::CommandFailed, err)),
-                },
-            },
+                    None => {
+                        return Err(AppError::new(
+                            ErrorType::CommandFailed,
+                            "Could not find the current commit".to_string(),
+                        ))
+                    }
+                };
+
+                // Priority is then given to a remote branch containing the current commit
+                if let Some(branch) =
+                    self.get_remote_branch_containing(commit_hash, remote, snapshot, git)?
+                {
+                    self.print_detached_source(format!("remote branch '{remote}/{branch}'"));
+                    return Ok(Cow::Owned(branch));
+                }
+
+                // Priority is then given to the current commit
+                let commit = if snapshot.is_bare() {
+                    Cow::Borrowed(commit_hash)
+                } else {
+                    self.handle_unpushed(commit_hash, remote, git)?
+                };
+                self.print_detached_source(format!("commit '{commit}'"));
+                Ok(Cow::Owned(commit.into_owned()))
+            }
         }
     }
 
-    fn get_git_url(&self, remote: &str, git: &impl GitTrait) -> Result<String, AppError> {
-        match git.is_valid_remote(remote)? {
-            GitOutput::Ok(output) => {
-                if output != remote {
-                    Ok(output)
-                } else {
-                    Err(AppError::new(
-                        ErrorType::MissingGitRemote,
-                        format!("Looks like your git remote isn't set for '{}'", remote),
-                    ))
+    /// Finds the branch on the remote that contains the commit, preferring the default branch
+    fn get_remote_branch_containing(
+        &self,
+        commit: &str,
+        remote: &str,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<Option<String>, AppError> {
+        // Bare clones have no remote-tracking branches
+        if snapshot.is_bare() {
+            return Ok(None);
+        }
+
+        let branches = match git.get_remote_branches_containing(remote, commit)? {
+            GitOutput::Ok(branches) => branches,
+            GitOutput::Err(_) => return Ok(None),
+        };
+        // Skip the symbolic '<remote>/HEAD -> <remote>/main' entry
+        let branches = branches
+            .lines()
+            .filter(|branch| !branch.contains(" -> "))
+            .filter_map(|branch| branch.trim().strip_prefix(&format!("{remote}/")))
+            .collect::<Vec<_>>();
+
+        Ok(snapshot
+            .get_default_branch(remote)
+            .filter(|default_branch| branches.contains(default_branch))
+            .or_else(|| branches.first().copied())
+            .map(String::from))
+    }
+
+    fn print_detached_source(&self, source: String) {
+        if self.is_verbose {
+            eprintln!("Detached HEAD resolved to {source}");
+        }
+    }
+
+    fn get_git_url(&self, remote: &str, snapshot: &RepoSnapshot) -> Result<String, AppError> {
+        match snapshot.get_remote_url(remote) {
+            Some(url) => Ok(url),
+            None => Err(AppError::MissingGitRemote {
+                remote: Some(remote.into()),
+                suggestion: suggest(remote, snapshot.remotes()).map(String::from),
+                message: format!("Looks like your git remote isn't set for '{remote}'"),
+            }),
+        }
+    }
+
+    /// Finds the submodule that the path, or the current working directory, lies within
+    /// Checked Out Branch Of Submodule -> Commit Pinned By Superproject
+    fn get_submodule(
+        &self,
+        git_url: &str,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<Option<Submodule>, AppError> {
+        if self.issue.is_some() || self.is_origin_pr {
+            return Ok(None);
+        }
+
+        let path = match self.path {
+            Some(path) => self.resolve_path(path, snapshot, git)?,
+            None => snapshot.prefix().into(),
+        };
+        if path.is_empty() {
+            return Ok(None);
+        }
+
+        // Submodules are recorded in the '.gitmodules' of the superproject
+        let gitmodules = match git.get_submodules()? {
+            GitOutput::Ok(gitmodules) => RepoSnapshot::parse(&gitmodules, "", ""),
+            GitOutput::Err(_) => return Ok(None),
+        };
+        let (name, submodule_path, inner_path) =
+            match gitmodules.entries().find_map(|(key, value)| {
+                let name = key.strip_prefix("submodule.")?.strip_suffix(".path")?;
+                let inner_path = path.strip_prefix(value.trim_end_matches('/'))?;
+                (inner_path.is_empty() || inner_path.starts_with('/'))
+                    .then(|| (name, value, inner_path.trim_start_matches('/')))
+            }) {
+                Some(submodule) => submodule,
+                None => return Ok(None),
+            };
+
+        // The url in the config of the superproject is set once the submodule is initialised
+        let url_key = format!("submodule.{name}.url");
+        let url = match snapshot
+            .config(&url_key)
+            .or_else(|| gitmodules.config(&url_key))
+        {
+            Some(url) if url.starts_with("./") || url.starts_with("../") => {
+                resolve_relative_url(git_url, url)
+            }
+            Some(url) => url.into(),
+            None => {
+                return Err(AppError::MissingGitRemote {
+                    remote: None,
+                    suggestion: None,
+                    message: format!("Looks like the url of submodule '{name}' isn't set"),
+                })
+            }
+        };
+
+        let branch = match (self.commit, git.get_top_level()?) {
+            (None, GitOutput::Ok(top_level)) => {
+                match git.get_submodule_branch(&format!("{top_level}/{submodule_path}"))? {
+                    GitOutput::Ok(branch) if !branch.is_empty() => Some(branch),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let reference = match branch {
+            Some(branch) => branch,
+            None => {
+                let commit = match self.commit {
+                    Some(commit) => self.resolve_commit(commit, snapshot, git)?,
+                    None => "HEAD".into(),
+                };
+                match git.get_tree_entry(&commit, submodule_path)? {
+                    GitOutput::Ok(gitlink) if !gitlink.is_empty() => gitlink,
+                    _ => {
+                        return Err(AppError::new(
+                            ErrorType::InvalidRevision,
+                            format!(
+                            "Could not find the commit of submodule '{name}' pinned at '{commit}'"
+                        ),
+                        ))
+                    }
                 }
             }
-            GitOutput::Err(err) => Err(AppError::new(ErrorType::CommandFailed, err)),
+        };
+
+        Ok(Some(Submodule {
+            url,
+            reference,
+            path: self.path.map(|_| inner_path.into()).unwrap_or_default(),
+        }))
+    }
+
+    fn generate_submodule_url(&self, submodule: &Submodule) -> Result<String, AppError> {
+        let url = self.parse_git_url(&submodule.url)?;
+        let mut open_url = format!(
+            "{}://{}/{}/tree/{}",
+            url.protocol,
+            url.domain,
+            url.path,
+            encode_path(&submodule.reference)
+        );
+
+        if !submodule.path.is_empty() {
+            open_url.push_str(format!("/{}", encode_path(&submodule.path)).as_str());
         }
+
+        Ok(open_url)
     }
 
     /*
@@ -187,10 +910,10 @@ impl<'a> GitView<'a> {
                         .trim_end_matches('/')
                         .trim_end_matches(".git"),
                 )),
-                Err(_) => Err(AppError::new(
-                    ErrorType::InvalidGitUrl,
-                    format!("Sorry, couldn't parse git url '{}'", git_url),
-                )),
+                Err(_) => Err(AppError::InvalidGitUrl {
+                    url: git_url.into(),
+                    message: format!("Sorry, couldn't parse git url '{git_url}'"),
+                }),
             }
         } else {
             match git_url.split_once(':') {
@@ -204,10 +927,10 @@ impl<'a> GitView<'a> {
 
                     Ok(Url::new(protocol, split_domain, path))
                 }
-                None => Err(AppError::new(
-                    ErrorType::InvalidGitUrl,
-                    format!("Sorry, couldn't parse git url '{}'", git_url),
-                )),
+                None => Err(AppError::InvalidGitUrl {
+                    url: git_url.into(),
+                    message: format!("Sorry, couldn't parse git url '{git_url}'"),
+                }),
             }
         }
     }
@@ -215,27 +938,26 @@ impl<'a> GitView<'a> {
     fn generate_final_url(
         &self,
         remote_ref: &str,
+        remote: &str,
         url: &Url,
+        snapshot: &RepoSnapshot,
         git: &impl GitTrait,
     ) -> Result<String, AppError> {
         let mut open_url = format!("{}://{}/{}", url.protocol, url.domain, url.path);
-        let escaped_remote_ref = escape_ascii_chars(remote_ref);
 
         if let Some(issue) = self.issue {
-            self.handle_issue_flag(issue, &escaped_remote_ref, &mut open_url)?;
-            return Ok(open_url);
+            return self.handle_issue_flag(issue, remote_ref, remote, url, snapshot, git);
         }
         if let Some(commit) = self.commit {
-            self.handle_commit_flag(commit, &mut open_url, git)?;
+            self.handle_commit_flag(commit, remote, &mut open_url, snapshot, git)?;
             return Ok(open_url);
         }
         if let Some(path) = self.path {
-            let prefix = format!("/tree/{}", escaped_remote_ref);
-            self.handle_path_flag(Some(prefix.as_str()), path, &mut open_url, git)?;
+            self.handle_path_flag(remote_ref, remote, path, &mut open_url, snapshot, git)?;
             return Ok(open_url);
         }
 
-        open_url.push_str(format!("/tree/{}", escaped_remote_ref).as_str());
+        open_url.push_str(format!("/tree/{}", encode_path(remote_ref)).as_str());
 
         Ok(open_url)
     }
@@ -244,522 +966,2169 @@ impl<'a> GitView<'a> {
         &self,
         issue: &str,
         remote_ref: &str,
-        open_url: &mut String,
-    ) -> Result<(), AppError> {
+        remote: &str,
+        url: &Url,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<String, AppError> {
+        let mut open_url = format!("{}://{}/{}", url.protocol, url.domain, url.path);
+
+        // Numeric issues always belong to the forge, anything else could be an external tracker key
+        if !issue.chars().all(char::is_numeric) {
+            let haystack = if issue == "branch" { remote_ref } else { issue };
+            if let Some(tracker_url) = self.get_issue_tracker_url(haystack, snapshot)? {
+                open_url = tracker_url;
+                return Ok(open_url);
+            }
+        }
+
         if issue == "branch" {
-            if let Some(issue_num) = capture_digits(remote_ref) {
-                open_url.push_str(format!("/issues/{}", issue_num).as_str());
-            } else {
-                open_url.push_str("/issues");
+            let issue_num = match snapshot.config("view.issuePattern") {
+                // A user configured pattern is expected to match, error out if it doesn't
+                Some(pattern) => match capture_issue_number(remote_ref, pattern)? {
+                    Some(issue_num) => Some(issue_num),
+                    None => {
+                        return Err(AppError::new(
+                            ErrorType::MissingIssueNumber,
+                            format!("Branch '{remote_ref}' does not match the issue pattern '{pattern}' set in 'view.issuePattern'"),
+                        ))
+                    }
+                },
+                None => capture_default_issue_number(remote_ref)?,
+            };
+
+            if let Some(issue_num) = issue_num {
+                open_url.push_str(format!("/issues/{}", encode_path(issue_num)).as_str());
+                return Ok(open_url);
+            }
+
+            // Branch has no issue number, fall back to the issues referenced in its commits
+            let base = match get_base_branch(remote, snapshot) {
+                Some(base) => base,
+                None => {
+                    eprintln!("Cannot find the default branch of '{remote}' to find the issues referenced in commits, defaulting to the issues");
+                    open_url.push_str("/issues");
+                    return Ok(open_url);
+                }
+            };
+            let messages = match git.get_commit_messages(&base)? {
+                GitOutput::Ok(messages) => messages,
+                GitOutput::Err(err) => return Err(AppError::new(ErrorType::CommandFailed, err)),
+            };
+
+            match capture_issue_references(&messages).as_slice() {
+                [] => open_url.push_str("/issues"),
+                [reference] => match reference.repository {
+                    Some(repository) => {
+                        open_url = format!(
+                            "{}://{}/{}/issues/{}",
+                            url.protocol, url.domain, repository, reference.number
+                        )
+                    }
+                    None => open_url.push_str(format!("/issues/{}", reference.number).as_str()),
+                },
+                references => {
+                    let references = references
+                        .iter()
+                        .map(|reference| reference.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    return Err(AppError::new(
+                        ErrorType::AmbiguousIssueReference,
+                        format!("Found multiple issues referenced in commits since '{base}': {references}\nPlease pick one with '--issue <number>'"),
+                    ));
+                }
             }
         } else {
-            open_url.push_str(format!("/issues/{}", issue).as_str());
+            open_url.push_str(format!("/issues/{}", encode_path(issue)).as_str());
         }
 
-        Ok(())
+        Ok(open_url)
+    }
+
+    /// Builds the url of the issue on the external tracker set in `view.issueTracker`
+    /// e.g. `https://jira.corp/browse/{key}` -> `https://jira.corp/browse/PROJ-1234`
+    fn get_issue_tracker_url(
+        &self,
+        haystack: &str,
+        snapshot: &RepoSnapshot,
+    ) -> Result<Option<String>, AppError> {
+        let tracker = match snapshot.config("view.issueTracker") {
+            Some(tracker) => tracker,
+            None => return Ok(None),
+        };
+
+        if !tracker.contains("{key}") {
+            return Err(AppError::new(
+                ErrorType::InvalidIssueTracker,
+                format!(
+                    "Issue tracker '{tracker}' set in 'view.issueTracker' must contain '{{key}}'"
+                ),
+            ));
+        }
+
+        let key_pattern = snapshot
+            .config("view.issueKeyPattern")
+            .unwrap_or(DEFAULT_ISSUE_KEY_PATTERN);
+
+        Ok(capture_issue_key(haystack, key_pattern)?
+            .map(|key| tracker.replace("{key}", &encode_path(key))))
     }
 
     fn handle_commit_flag(
         &self,
         commit: &str,
+        remote: &str,
         open_url: &mut String,
+        snapshot: &RepoSnapshot,
         git: &impl GitTrait,
     ) -> Result<(), AppError> {
-        if commit == "current" {
-            match git.get_current_commit()? {
-                GitOutput::Ok(hash) => {
-                    open_url.push_str(format!("/tree/{}", hash).as_str());
-                }
-                GitOutput::Err(err) => return Err(AppError::new(ErrorType::CommandFailed, err)),
-            };
-        } else {
-            open_url.push_str(format!("/tree/{}", commit).as_str());
+        let commit = self.resolve_commit(commit, snapshot, git)?;
+
+        if self.is_origin_pr {
+            let pull_request = self.get_origin_pull_request(&commit, remote, snapshot, git)?;
+            open_url.push_str(&pull_request);
+            return Ok(());
         }
 
+        let commit = self.verify_pushed(&commit, remote, snapshot, git)?;
+        open_url.push_str(format!("/tree/{}", encode_path(&commit)).as_str());
+
         // path can still be appended after commit hash
         if let Some(path) = self.path {
-            // prefix is empty because trailing slash will be added
-            self.handle_path_flag(None, path, open_url, git)?;
+            let path = self.resolve_path(path, snapshot, git)?;
+            let path = self.follow_renames(&commit, &path, git)?;
+
+            if !path.is_empty() {
+                open_url.push_str(format!("/{}", encode_path(&path)).as_str());
+            }
         }
 
         Ok(())
     }
 
-    fn handle_path_flag(
+    /// Maps the path to its name at the commit, following renames between the commit and HEAD
+    fn follow_renames(
         &self,
-        prefix: Option<&str>,
+        commit: &str,
         path: &str,
-        open_url: &mut String,
         git: &impl GitTrait,
-    ) -> Result<(), AppError> {
-        if path == "current-working-directory" {
-            match git.get_current_working_directory()? {
-                GitOutput::Ok(cwd) => {
-                    // If the current working directory is not the root of the repo, append it
-                    if !cwd.is_empty() {
-                        open_url.push_str(format!("{}/{}", prefix.unwrap(), cwd).as_str());
+    ) -> Result<String, AppError> {
+        if path.is_empty() {
+            return Ok(path.into());
+        }
+
+        let trailing_slash = if path.ends_with('/') { "/" } else { "" };
+        let mut name = path.trim_end_matches('/').to_string();
+
+        // Changes are listed from newest to oldest, e.g. 'R100\t<old name>\t<new name>'
+        if let GitOutput::Ok(changes) = git.get_renames(commit, &name)? {
+            for change in changes.lines() {
+                let mut fields = change.split('\t');
+                if let (Some(status), Some(old_name), Some(new_name)) =
+                    (fields.next(), fields.next(), fields.next())
+                {
+                    if status.starts_with('R') && new_name == name {
+                        name = old_name.into();
+                    }
+                }
+            }
+        }
+
+        match git.get_tree_entry(commit, &name)? {
+            GitOutput::Ok(entry) if !entry.is_empty() => {
+                if name != path.trim_end_matches('/') {
+                    eprintln!("Path '{path}' was renamed, defaulting to '{name}' at '{commit}'");
+                }
+                Ok(format!("{name}{trailing_slash}"))
+            }
+            _ => Err(AppError::new(
+                ErrorType::MissingPath,
+                format!("Path '{path}' did not exist at commit '{commit}'"),
+            )),
+        }
+    }
+
+    fn resolve_commit(
+        &self,
+        commit: &str,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<String, AppError> {
+        if commit == "current" {
+            return match snapshot.current_commit() {
+                Some(hash) => Ok(hash.into()),
+                None => Err(AppError::new(
+                    ErrorType::CommandFailed,
+                    "Could not find the current commit".to_string(),
+                )),
+            };
+        }
+
+        // Resolve revision expressions, e.g. 'HEAD~3', 'v1.2^{}', '@{upstream}', to the full hash
+        match git.get_revision(commit)? {
+            GitOutput::Ok(hash) if !hash.is_empty() => Ok(hash),
+            _ => Err(AppError::new(
+                ErrorType::InvalidRevision,
+                format!("Could not resolve '{commit}' to a commit, expected a hash, branch, tag or expression such as 'HEAD~3'"),
+            )),
+        }
+    }
+
+    /// Ensures the commit is reachable from the remote, otherwise the forge would respond with a 404
+    fn verify_pushed<'c>(
+        &self,
+        commit: &'c str,
+        remote: &str,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<Cow<'c, str>, AppError> {
+        // Bare clones have no remote-tracking branches, every commit in them came from the remote
+        if snapshot.is_bare() {
+            return Ok(Cow::Borrowed(commit));
+        }
+
+        if let GitOutput::Ok(branches) = git.get_remote_branches_containing(remote, commit)? {
+            if !branches.is_empty() {
+                return Ok(Cow::Borrowed(commit));
+            }
+        }
+
+        self.handle_unpushed(commit, remote, git)
+    }
+
+    fn handle_unpushed<'c>(
+        &self,
+        commit: &'c str,
+        remote: &str,
+        git: &impl GitTrait,
+    ) -> Result<Cow<'c, str>, AppError> {
+        match self.unpushed {
+            Unpushed::Warn => {
+                eprintln!("Commit '{commit}' has not been pushed to '{remote}', it may not exist on the remote");
+                Ok(Cow::Borrowed(commit))
+            }
+            Unpushed::Refuse => Err(AppError::UnpushedCommit {
+                commit: commit.into(),
+                remote: remote.into(),
+                ancestors: false,
+            }),
+            // Boundary commits are prefixed with '-' and are the newest pushed ancestors
+            Unpushed::Ancestor => match git.get_pushed_boundary(remote, commit)? {
+                GitOutput::Ok(revisions) => match revisions
+                    .lines()
+                    .find_map(|revision| revision.strip_prefix('-'))
+                {
+                    Some(ancestor) => {
+                        eprintln!("Commit '{commit}' has not been pushed to '{remote}', defaulting to '{ancestor}'");
+                        Ok(Cow::Owned(ancestor.into()))
+                    }
+                    None => Err(AppError::UnpushedCommit {
+                        commit: commit.into(),
+                        remote: remote.into(),
+                        ancestors: true,
+                    }),
+                },
+                GitOutput::Err(err) => Err(AppError::new(ErrorType::CommandFailed, err)),
+            },
+        }
+    }
+
+    /// Finds the path of the pull request, or merge request on GitLab, that introduced the commit
+    /// Squashed Commit Title '(#N)' -> First Merge Commit 'Merge pull request #N' Into Default Branch
+    fn get_origin_pull_request(
+        &self,
+        commit: &str,
+        remote: &str,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<String, AppError> {
+        // Priority goes to the commit itself, it could be squashed or the merge commit
+        if let GitOutput::Ok(message) = git.get_commit_message(commit)? {
+            if let Some(pull_request) = capture_pull_request_path(&message) {
+                return Ok(pull_request);
+            }
+        }
+
+        // Priority then goes to the first merge commit between the commit and the default branch
+        if let Some(base) = get_base_branch(remote, snapshot) {
+            match git.get_merge_messages(commit, &base)? {
+                GitOutput::Ok(messages) => {
+                    if let Some(pull_request) =
+                        messages.split('\0').find_map(capture_pull_request_path)
+                    {
+                        return Ok(pull_request);
                     }
                 }
                 GitOutput::Err(err) => return Err(AppError::new(ErrorType::CommandFailed, err)),
             }
-        } else if let Some(prefix) = prefix {
-            open_url.push_str(format!("{}/{}", prefix, path).as_str());
+        }
+
+        Err(AppError::MissingPullRequest {
+            commit: commit.into(),
+        })
+    }
+
+    fn handle_path_flag(
+        &self,
+        remote_ref: &str,
+        remote: &str,
+        path: &str,
+        open_url: &mut String,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<(), AppError> {
+        let path = self.resolve_path(path, snapshot, git)?;
+        let path = self.verify_path_exists(&path, remote_ref, remote, snapshot, git)?;
+
+        // The current working directory at the root of the repo has nothing to append, a path
+        // given by the user is still viewed at the ref, e.g. '--branch dev --path .'
+        if path.is_empty() && self.path == Some("current-working-directory") {
+            return Ok(());
+        }
+        open_url.push_str(format!("/tree/{}", encode_path(remote_ref)).as_str());
+        if !path.is_empty() {
+            open_url.push_str(format!("/{}", encode_path(&path)).as_str());
+        }
+
+        Ok(())
+    }
+
+    /// Ensures the path exists in the ref being opened, otherwise the forge would respond with a 404
+    fn verify_path_exists<'p>(
+        &self,
+        path: &'p str,
+        remote_ref: &str,
+        remote: &str,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<Cow<'p, str>, AppError> {
+        let reference = snapshot.local_reference(remote, remote_ref);
+        // e.g. 'origin/main' for a branch
+        let name = reference
+            .strip_prefix("refs/remotes/")
+            .unwrap_or(&reference)
+            .to_string();
+
+        let mut existing = path.trim_end_matches('/');
+        while !existing.is_empty() {
+            match git.get_tree_listing(&reference, existing)? {
+                GitOutput::Ok(listing) if !listing.is_empty() => break,
+                GitOutput::Ok(_) => {}
+                // The ref isn't known locally, so there is nothing to verify the path against
+                GitOutput::Err(_) => return Ok(Cow::Borrowed(path)),
+            }
+
+            if self.missing_path == MissingPath::Refuse {
+                let suggestion = match git.get_tracked_paths(&reference)? {
+                    GitOutput::Ok(paths) => suggest(path, paths.lines()).map(String::from),
+                    GitOutput::Err(_) => None,
+                };
+                return Err(AppError::MissingRemotePath {
+                    path: path.into(),
+                    reference: name,
+                    suggestion,
+                });
+            }
+            existing = existing
+                .rsplit_once('/')
+                .map(|(parent, _)| parent)
+                .unwrap_or_default();
+        }
+
+        if existing == path.trim_end_matches('/') {
+            return Ok(Cow::Borrowed(path));
+        }
+        if existing.is_empty() {
+            eprintln!("Path '{path}' does not exist on '{name}', defaulting to the root of the repository");
+            return Ok(Cow::Borrowed(""));
+        }
+        eprintln!("Path '{path}' does not exist on '{name}', defaulting to '{existing}/'");
+        Ok(Cow::Owned(format!("{existing}/")))
+    }
+
+    /// Resolves the path relative to the root of the repository
+    /// Absolute Path -> Relative To Top Level, Relative Path -> Relative To Current Working Directory
+    fn resolve_path(
+        &self,
+        path: &str,
+        snapshot: &RepoSnapshot,
+        git: &impl GitTrait,
+    ) -> Result<String, AppError> {
+        let outside_repository = || AppError::PathOutsideRepository { path: path.into() };
+
+        if !Path::new(path).is_absolute() && path != "current-working-directory" {
+            return normalize_path(snapshot.prefix(), path).ok_or_else(outside_repository);
+        }
+
+        // Bare repositories have no working directory, only paths from the root can be viewed
+        if snapshot.is_bare() {
+            let target = match path {
+                "current-working-directory" => "the current working directory".into(),
+                path => format!("'{path}'"),
+            };
+            return Err(AppError::new(
+                ErrorType::BareRepository,
+                format!("Cannot view {target} of a bare repository, try a path relative to the root of the repository"),
+            ));
+        }
+
+        if path == "current-working-directory" {
+            return Ok(snapshot.prefix().into());
+        }
+
+        let top_level = match git.get_top_level()? {
+            GitOutput::Ok(top_level) => top_level,
+            GitOutput::Err(err) => return Err(AppError::new(ErrorType::CommandFailed, err)),
+        };
+        // The top level has symlinks resolved, so the path must be too if it exists
+        let absolute = Path::new(path)
+            .canonicalize()
+            .unwrap_or_else(|_| Path::new(path).into());
+        let top_level = Path::new(&top_level)
+            .canonicalize()
+            .unwrap_or_else(|_| Path::new(&top_level).into());
+
+        match absolute.strip_prefix(top_level) {
+            Ok(relative) => {
+                let relative = relative
+                    .components()
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let trailing_slash = if path.ends_with('/') { "/" } else { "" };
+                normalize_path("", &format!("{relative}{trailing_slash}"))
+                    .ok_or_else(outside_repository)
+            }
+            Err(_) => Err(outside_repository()),
+        }
+    }
+}
+
+/// The submodule that the viewed path lies within
+#[derive(Debug, PartialEq)]
+struct Submodule {
+    url: String,
+    /// The checked out branch, or the commit pinned by the superproject
+    reference: String,
+    /// Path within the submodule
+    path: String,
+}
+
+/// The branch that the commits of the current branch are compared against, the default branch
+/// of the remote, or HEAD in bare repositories where the branches are those of the remote
+fn get_base_branch(remote: &str, snapshot: &RepoSnapshot) -> Option<String> {
+    if snapshot.is_bare() {
+        return Some("HEAD".into());
+    }
+    snapshot
+        .find_default_branch(remote)
+        .map(|default_branch| format!("{remote}/{default_branch}"))
+}
+
+/// Asks which of the remotes to view, the prompt is written to stderr so that the output of
+/// '--print' can still be piped
+fn prompt_remote(remotes: &[&str]) -> Result<String, AppError> {
+    eprintln!("Looks like there's no 'origin' remote, which remote should be viewed?");
+    for (number, remote) in remotes.iter().enumerate() {
+        eprintln!("  {}) {}", number + 1, remote);
+    }
+    eprint!("Remote [1-{}]: ", remotes.len());
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+
+    // Accept either the number or the name of the remote
+    match answer
+        .parse::<usize>()
+        .ok()
+        .and_then(|number| remotes.get(number.checked_sub(1)?))
+        .or_else(|| remotes.iter().find(|remote| **remote == answer))
+    {
+        Some(remote) => Ok(remote.to_string()),
+        None => Err(AppError::MissingGitRemote {
+            remote: None,
+            suggestion: suggest(answer, remotes.iter().copied()).map(String::from),
+            message: format!("'{answer}' is not one of the remotes"),
+        }),
+    }
+}
+
+/// The candidate closest to the target, if it's close enough to be a typo of it
+fn suggest<'c>(target: &str, candidates: impl IntoIterator<Item = &'c str>) -> Option<&'c str> {
+    let max_distance = (target.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != target)
+        .map(|candidate| (edit_distance(target, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Damerau-Levenshtein distance, the number of single character edits or swaps of adjacent
+/// characters to turn one string into the other
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Resolves a submodule url relative to the url of the superproject, the same as git
+/// e.g. (`git@github.com:sgoudham/git-view.git`, `../other.git`) -> `git@github.com:sgoudham/other.git`
+fn resolve_relative_url(base: &str, relative: &str) -> String {
+    let mut base = base.trim_end_matches('/');
+    let mut relative = relative;
+    let mut separator = '/';
+
+    loop {
+        if let Some(rest) = relative.strip_prefix("./") {
+            relative = rest;
+        } else if let Some(rest) = relative.strip_prefix("../") {
+            relative = rest;
+            // Going up past the path of an scp-like url leaves the host and ':' separator
+            match base.rfind(['/', ':']) {
+                Some(index) => {
+                    separator = if base[index..].starts_with(':') {
+                        ':'
+                    } else {
+                        '/'
+                    };
+                    base = &base[..index];
+                }
+                None => base = "",
+            }
         } else {
-            open_url.push_str(format!("/{}", path).as_str());
+            break;
+        }
+    }
+
+    format!("{base}{separator}{relative}")
+}
+
+/// Patterns tried in order when `view.issuePattern` is not configured, the first capture
+/// group of each pattern holds the issue number
+///
+/// e.g. `ISSUE-123`, `123-description`, `feature/gh-123`, `issue/123`, falling back to the
+/// first run of digits, e.g. `feature_123`, `bug42`
+const DEFAULT_ISSUE_PATTERNS: [&str; 5] = [
+    r"^[A-Za-z][A-Za-z0-9]*-(\d+)\b",
+    r"^(\d+)\b",
+    r"(?i)(?:^|/)(?:gh|issues?)[-/]?(\d+)\b",
+    r"\b(\d+)\b",
+    r"(\d+)",
+];
+
+/// Pattern used when `view.issueTracker` is configured without `view.issueKeyPattern`
+/// e.g. `PROJ-1234`, `ENG-42`
+const DEFAULT_ISSUE_KEY_PATTERN: &str = r"\b[A-Z][A-Z0-9]+-\d+\b";
+
+/// The path of the pull request that a merge or squashed commit came from, e.g. `/pull/42` for
+/// `Merge pull request #42 from x/y` or `Fix the thing (#42)` on GitHub, and
+/// `/-/merge_requests/42` for `See merge request group/project!42` on GitLab
+fn capture_pull_request_path(message: &str) -> Option<String> {
+    let subject = message.trim_start().lines().next().unwrap_or_default();
+    let pull_request = Regex::new(r"^Merge pull request #(\d+)|\(#(\d+)\)\s*$").unwrap();
+    if let Some(number) = pull_request
+        .captures(subject)
+        .and_then(|captures| captures.get(1).or_else(|| captures.get(2)))
+    {
+        return Some(format!("/pull/{}", number.as_str()));
+    }
+
+    let merge_request = Regex::new(r"(?m)^See merge request \S*!(\d+)\s*$").unwrap();
+    merge_request
+        .captures(message)
+        .map(|captures| format!("/-/merge_requests/{}", &captures[1]))
+}
+
+/// An issue referenced within a commit message
+/// e.g. `Fixes #123`, `Closes: GH-45`, `Refs sgoudham/git-view#12`
+#[derive(Debug, PartialEq)]
+struct IssueReference<'a> {
+    repository: Option<&'a str>,
+    number: &'a str,
+}
+
+impl Display for IssueReference<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.repository {
+            Some(repository) => write!(f, "{}#{}", repository, self.number),
+            None => write!(f, "#{}", self.number),
+        }
+    }
+}
+
+fn capture_issue_references(messages: &str) -> Vec<IssueReference<'_>> {
+    let regex = Regex::new(
+        r"(?i)\b(?:fix(?:e[sd])?|close[sd]?|resolve[sd]?|refs?)\b:?\s+(?:([\w.-]+/[\w.-]+)#|#|gh-)(\d+)\b",
+    )
+    .unwrap();
+
+    let mut references = Vec::new();
+    for captures in regex.captures_iter(messages) {
+        let reference = IssueReference {
+            repository: captures.get(1).map(|repository| repository.as_str()),
+            number: captures.get(2).unwrap().as_str(),
+        };
+        if !references.contains(&reference) {
+            references.push(reference);
+        }
+    }
+
+    references
+}
+
+fn capture_issue_key<'r>(haystack: &'r str, pattern: &str) -> Result<Option<&'r str>, AppError> {
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(err) => {
+            return Err(AppError::new(
+                ErrorType::InvalidIssuePattern,
+                format!("Sorry, couldn't parse issue key pattern '{pattern}': {err}"),
+            ))
+        }
+    };
+
+    // The key is the first capture group if there is one, otherwise the whole match
+    Ok(regex
+        .captures(haystack)
+        .and_then(|captures| captures.get(1).or_else(|| captures.get(0)))
+        .map(|key| key.as_str()))
+}
+
+fn capture_default_issue_number(remote_ref: &str) -> Result<Option<&str>, AppError> {
+    for pattern in DEFAULT_ISSUE_PATTERNS {
+        if let Some(issue_num) = capture_issue_number(remote_ref, pattern)? {
+            return Ok(Some(issue_num));
+        }
+    }
+
+    Ok(None)
+}
+
+fn capture_issue_number<'r>(
+    remote_ref: &'r str,
+    pattern: &str,
+) -> Result<Option<&'r str>, AppError> {
+    let regex = match Regex::new(pattern) {
+        Ok(regex) if regex.captures_len() > 1 => regex,
+        Ok(_) => {
+            return Err(AppError::new(
+                ErrorType::InvalidIssuePattern,
+                format!(
+                    "Issue pattern '{pattern}' must contain a capture group for the issue number"
+                ),
+            ))
+        }
+        Err(err) => {
+            return Err(AppError::new(
+                ErrorType::InvalidIssuePattern,
+                format!("Sorry, couldn't parse issue pattern '{pattern}': {err}"),
+            ))
+        }
+    };
+
+    Ok(regex
+        .captures(remote_ref)
+        .and_then(|captures| captures.get(1))
+        .map(|issue_num| issue_num.as_str()))
+}
+
+/// Joins the path onto the directory and resolves `.` and `..` without touching the file
+/// system, a trailing slash is kept, e.g. (`src/`, `../docs/`) -> `docs/`
+///
+/// Returns [`None`] if the path escapes the root of the repository
+fn normalize_path(directory: &str, path: &str) -> Option<String> {
+    let mut segments = Vec::new();
+    for segment in directory.split('/').chain(path.split('/')) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    let mut normalized = segments.join("/");
+    if !normalized.is_empty()
+        && (path.ends_with('/') || (path.is_empty() && directory.ends_with('/')))
+    {
+        normalized.push('/');
+    }
+
+    Some(normalized)
+}
+
+/// Characters that must be percent-encoded within a path segment, RFC 3986 unreserved
+/// characters and the sub-delimiters that forges treat literally are left as is
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'!')
+    .remove(b'$')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'*')
+    .remove(b',')
+    .remove(b':')
+    .remove(b'@');
+
+/// Percent-encodes every segment of the path, keeping '/' as the separator
+fn encode_path(path: &str) -> Cow<'_, str> {
+    let encoded_path = path
+        .split('/')
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if encoded_path == path {
+        Cow::Borrowed(path)
+    } else {
+        Cow::Owned(encoded_path)
+    }
+}
+
+#[cfg(test)]
+mod lib_tests {
+    mod repo_snapshot {
+        use test_case::test_case;
+
+        use crate::{
+            error::ErrorType,
+            git::{GitOutput, MockGitTrait},
+            snapshot::RepoSnapshot,
+        };
+
+        #[test]
+        fn is_valid_repository() {
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_head()
+                .returning(|| Ok(GitOutput::Ok("src/\nhash\nrefs/heads/main".into())));
+            mock.expect_get_config_list()
+                .returning(|| Ok(GitOutput::Ok("branch.main.remote\norigin\0".into())));
+            mock.expect_get_refs()
+                .returning(|| Ok(GitOutput::Ok("refs/remotes/origin/main\0hash\0\0".into())));
+
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+
+            assert_eq!(snapshot.prefix(), "src/");
+            assert_eq!(snapshot.current_commit(), Some("hash"));
+            assert_eq!(snapshot.current_branch(), Some("main"));
+            assert_eq!(snapshot.config("branch.main.remote"), Some("origin"));
+            assert!(snapshot.has_remote_tracking_branch("origin", "main"));
+        }
+
+        #[test]
+        fn is_not_valid_repository() {
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_head()
+                .returning(|| Ok(GitOutput::Err("Error".into())));
+
+            let snapshot = RepoSnapshot::new(&mock);
+
+            assert!(snapshot.is_err());
+            let error = snapshot.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::MissingGitRepository);
+            assert_eq!(
+                error.to_string(),
+                "Looks like you're not in a valid git repository!"
+            );
+        }
+
+        #[test]
+        fn is_bare_repository() {
+            let snapshot = RepoSnapshot::parse("", "true\n\nhash\nrefs/heads/main", "");
+
+            assert!(snapshot.is_bare());
+            assert_eq!(snapshot.prefix(), "");
+            assert_eq!(snapshot.current_branch(), Some("main"));
+        }
+
+        #[test_case("main",   "refs/remotes/origin/main" ; "branch")]
+        #[test_case("v1.0.0", "v1.0.0"                   ; "tag")]
+        #[test_case("abc123", "abc123"                   ; "commit")]
+        fn is_local_reference(reference: &str, expected_reference: &str) {
+            let snapshot = RepoSnapshot::parse(
+                "",
+                "",
+                "refs/remotes/origin/main\0hash\0\0\nrefs/tags/v1.0.0\0hash\0\0",
+            );
+
+            assert_eq!(
+                snapshot.local_reference("origin", reference),
+                expected_reference
+            );
+        }
+
+        #[test]
+        fn is_unborn_head() {
+            let snapshot = RepoSnapshot::parse("", "false\n\n\nrefs/heads/main", "");
+
+            assert!(!snapshot.is_bare());
+            assert_eq!(snapshot.current_commit(), None);
+            assert_eq!(snapshot.current_branch(), Some("main"));
+        }
+
+        #[test]
+        fn is_linked_worktree() {
+            let snapshot = RepoSnapshot::parse("", "false\nsrc/\nhash\nrefs/heads/feature", "");
+
+            assert!(!snapshot.is_bare());
+            assert_eq!(snapshot.prefix(), "src/");
+            assert_eq!(snapshot.current_branch(), Some("feature"));
+        }
+
+        #[test]
+        fn is_detached_at_root() {
+            let snapshot = RepoSnapshot::parse("", "hash\nHEAD", "");
+
+            assert_eq!(snapshot.prefix(), "");
+            assert_eq!(snapshot.current_commit(), Some("hash"));
+            assert_eq!(snapshot.current_branch(), None);
+        }
+
+        #[test_case("branch.Feature.pushRemote",    Some("fork")    ; "with camel case name")]
+        #[test_case("BRANCH.Feature.PUSHREMOTE",    Some("fork")    ; "with upper case section")]
+        #[test_case("branch.feature.pushRemote",    None            ; "with case sensitive subsection")]
+        #[test_case("remote.pushDefault",           Some("second")  ; "with last value winning")]
+        #[test_case("core.bare",                    Some("true")    ; "with implicit value")]
+        fn config(key: &str, expected_value: Option<&str>) {
+            let snapshot = RepoSnapshot::parse(
+                "branch.Feature.pushremote\nfork\0remote.pushdefault\nfirst\0remote.pushdefault\nsecond\0core.bare\0",
+                "",
+                "",
+            );
+
+            assert_eq!(snapshot.config(key), expected_value);
+        }
+
+        #[test]
+        fn tags_and_remote_branches() {
+            let snapshot = RepoSnapshot::parse(
+                "",
+                "hash\nHEAD",
+                "refs/remotes/origin/HEAD\0hash\0\0refs/remotes/origin/main\n\
+                 refs/remotes/origin/main\0hash\0\0\n\
+                 refs/tags/v2.0.0\0tag_object\0hash\0\n\
+                 refs/tags/v1.0.0\0hash\0\0\n\
+                 refs/tags/v0.1.0\0other\0\0",
+            );
+
+            assert_eq!(snapshot.current_tag(), Some("v1.0.0"));
+            assert_eq!(snapshot.get_default_branch("origin"), Some("main"));
+            assert_eq!(snapshot.get_default_branch("upstream"), None);
+            assert!(snapshot.has_remote_tracking_branch("origin", "main"));
+            assert!(!snapshot.has_remote_tracking_branch("origin", "feature"));
+        }
+
+        #[test_case("git@github.com:sgoudham/git-view.git",     "git@github.com:sgoudham/git-view.git"     ; "without rewrite")]
+        #[test_case("gh:sgoudham/git-view.git",                 "https://github.com/sgoudham/git-view.git" ; "with rewrite")]
+        #[test_case("gh:work/sgoudham/git-view.git",            "git@work.com:sgoudham/git-view.git"       ; "with longest rewrite")]
+        fn remote_url(url: &str, expected_url: &str) {
+            let snapshot = RepoSnapshot::parse(
+                &format!(
+                    "remote.origin.url\n{url}\0\
+                     url.https://github.com/.insteadof\ngh:\0\
+                     url.git@work.com:.insteadof\ngh:work/\0"
+                ),
+                "",
+                "",
+            );
+
+            assert_eq!(snapshot.get_remote_url("origin").unwrap(), expected_url);
+            assert_eq!(snapshot.get_remote_url("upstream"), None);
+        }
+    }
+
+    mod app_error {
+        use std::{error::Error, io};
+
+        use test_case::test_case;
+
+        use crate::error::{AppError, ErrorType};
+
+        #[test]
+        fn is_typed_error_displayed() {
+            let error = AppError::MissingGitRemote {
+                remote: Some("upstrem".into()),
+                suggestion: Some("upstream".into()),
+                message: "Looks like your git remote isn't set for 'upstrem'".into(),
+            };
+
+            assert_eq!(error.error_type(), ErrorType::MissingGitRemote);
+            assert_eq!(
+                error.to_string(),
+                "Looks like your git remote isn't set for 'upstrem', did you mean 'upstream'?\nView it with '--remote upstream' or make it the default with 'git config open.default.remote upstream'"
+            );
+            assert!(error.source().is_none());
+        }
+
+        #[test]
+        fn is_io_error_source_chained() {
+            let error = AppError::from(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+
+            assert_eq!(error.error_type(), ErrorType::IOError);
+            assert_eq!(error.to_string(), "no such file");
+            assert_eq!(error.source().unwrap().to_string(), "no such file");
+        }
+
+        #[test_case(ErrorType::CommandFailed, 1 ; "command failed")]
+        #[test_case(ErrorType::MissingGitRepository, 3 ; "not a repository")]
+        #[test_case(ErrorType::MissingGitRemote, 4 ; "no remote")]
+        #[test_case(ErrorType::InvalidGitUrl, 5 ; "unparseable url")]
+        #[test_case(ErrorType::UnpushedCommit, 6 ; "unpushed commit")]
+        #[test_case(ErrorType::MissingRemotePath, 7 ; "missing path")]
+        #[test_case(ErrorType::MissingIssueNumber, 8 ; "missing issue")]
+        #[test_case(ErrorType::BrowserLaunchFailed, 9 ; "browser failed to launch")]
+        #[test_case(ErrorType::BareRepository, 10 ; "bare repository")]
+        fn is_exit_code(error_type: ErrorType, expected_code: i32) {
+            assert_eq!(error_type.exit_code(), expected_code);
+        }
+
+        #[test]
+        fn is_browser_launch_failure_source_chained() {
+            let error = AppError::BrowserLaunchFailed {
+                url: "https://github.com/sgoudham/git-view".into(),
+                source: io::Error::new(io::ErrorKind::NotFound, "no browser found"),
+            };
+
+            assert_eq!(error.exit_code(), 9);
+            assert_eq!(
+                error.to_string(),
+                "Could not open 'https://github.com/sgoudham/git-view' in a browser: no browser found"
+            );
+            assert_eq!(error.source().unwrap().to_string(), "no browser found");
+        }
+
+        #[test]
+        fn is_converted_from_error_type() {
+            let error = AppError::from((ErrorType::BareRepository, "bare".to_string()));
+
+            assert_eq!(
+                error,
+                AppError::new(ErrorType::BareRepository, "bare".into())
+            );
+            assert_eq!(error.error_type(), ErrorType::BareRepository);
+            assert_eq!(error.to_string(), "bare");
+        }
+
+        #[test]
+        fn is_converted_to_other_for_typed_category() {
+            let error = AppError::from((ErrorType::MissingGitRemote, "no remote".to_string()));
+
+            assert!(matches!(
+                error,
+                AppError::Other {
+                    error_type: ErrorType::MissingGitRemote,
+                    ..
+                }
+            ));
+            assert_eq!(error.exit_code(), 4);
+            assert_eq!(error.to_string(), "no remote");
+        }
+    }
+
+    mod view_repository {
+        use std::borrow::Cow;
+
+        use crate::{
+            error::{AppError, ErrorType},
+            git::{GitOutput, Local, MockGitTrait},
+            snapshot::RepoSnapshot,
+            GitView,
+        };
+
+        fn mock_repository(config: &'static str) -> MockGitTrait {
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_head()
+                .returning(|| Ok(GitOutput::Ok("false\n\nhash\nrefs/heads/main".into())));
+            mock.expect_get_config_list()
+                .returning(move || Ok(GitOutput::Ok(config.into())));
+            mock.expect_get_refs().returning(|| {
+                Ok(GitOutput::Ok(
+                    "refs/remotes/github/main\0hash\0\0\nrefs/remotes/mirror/main\0hash\0\0".into(),
+                ))
+            });
+
+            mock
+        }
+
+        #[test]
+        fn is_all_remotes_skipping_invalid_url() {
+            let handler = GitView::builder()
+                .with_all_remotes(true)
+                .with_print(true)
+                .build();
+            let mock = mock_repository(
+                "remote.github.url\ngit@github.com:sgoudham/git-view.git\0remote.mirror.url\n/srv/git/git-view.git\0",
+            );
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+            let local = Local::Branch(Cow::Borrowed("main"));
+
+            let actual_urls = handler.generate_all_remote_urls(&local, &snapshot, &mock);
+
+            assert_eq!(
+                actual_urls.unwrap(),
+                vec!["https://github.com/sgoudham/git-view/tree/main"]
+            );
+        }
+
+        #[test]
+        fn is_all_remotes_skipping_unfetched_remote() {
+            let handler = GitView::builder()
+                .with_all_remotes(true)
+                .with_print(true)
+                .build();
+            // 'backup' has no remote-tracking branches, so there is no branch to view on it
+            let mock = mock_repository(
+                "remote.backup.url\ngit@github.com:backup/git-view.git\0remote.github.url\ngit@github.com:sgoudham/git-view.git\0remote.mirror.url\ngit@gitlab.com:sgoudham/git-view.git\0",
+            );
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+            let local = Local::Branch(Cow::Borrowed("main"));
+
+            let actual_urls = handler.generate_all_remote_urls(&local, &snapshot, &mock);
+
+            assert_eq!(
+                actual_urls.unwrap(),
+                vec![
+                    "https://github.com/sgoudham/git-view/tree/main",
+                    "https://gitlab.com/sgoudham/git-view/tree/main"
+                ]
+            );
+        }
+
+        #[test]
+        fn is_all_remotes_propagating_invalid_commit() {
+            let handler = GitView::builder()
+                .with_all_remotes(true)
+                .with_commit("nope")
+                .with_print(true)
+                .build();
+            let mut mock = mock_repository(
+                "remote.github.url\nhttps://github.com/sgoudham/git-view.git\0remote.mirror.url\nhttps://gitlab.com/sgoudham/git-view.git\0",
+            );
+            mock.expect_get_revision()
+                .returning(|_| Ok(GitOutput::Err("".into())));
+
+            let actual_result = handler.view_repository(mock);
+
+            assert!(actual_result.is_err());
+            assert_eq!(
+                actual_result.unwrap_err().error_type(),
+                ErrorType::InvalidRevision
+            );
+        }
+
+        #[test]
+        fn is_all_remotes_without_valid_url() {
+            let handler = GitView::builder()
+                .with_all_remotes(true)
+                .with_print(true)
+                .build();
+            let mock = mock_repository("remote.mirror.url\n/srv/git/git-view.git\0");
+
+            let actual_result = handler.view_repository(mock);
+
+            assert!(actual_result.is_err());
+            let error = actual_result.unwrap_err();
+            assert!(matches!(
+                error,
+                AppError::MissingGitRemote { remote: None, .. }
+            ));
+            assert_eq!(error.error_type(), ErrorType::MissingGitRemote);
+            assert_eq!(
+                error.to_string(),
+                "Looks like none of your git remotes can be viewed"
+            );
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn is_deprecated_constructor_delegating_to_builder() {
+            let handler = GitView::new(None, Some("github"), None, None, None, true);
+            let mock =
+                mock_repository("remote.github.url\nhttps://github.com/sgoudham/git-view.git\0");
+
+            assert_eq!(handler.remote, Some("github"));
+            assert!(handler.is_print);
+            assert!(handler.view_repository(mock).is_ok());
+        }
+    }
+
+    mod known_repositories {
+        use std::{env, path::PathBuf};
+
+        use crate::{
+            git::{GitOutput, MockGitTrait},
+            GitView,
+        };
+
+        #[test]
+        fn is_listed_in_order() {
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_config_list().returning(|| {
+                Ok(GitOutput::Ok(
+                    "view.repositories\n/srv/git-view\0remote.origin.url\nurl\0view.repositories\n~/projects/lib\0".into(),
+                ))
+            });
+
+            let actual_repositories = GitView::known_repositories(&mock);
+
+            let home = PathBuf::from(env::var_os("HOME").unwrap());
+            assert_eq!(
+                actual_repositories.unwrap(),
+                vec![PathBuf::from("/srv/git-view"), home.join("projects/lib")]
+            );
+        }
+
+        #[test]
+        fn is_empty_without_config() {
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_config_list()
+                .returning(|| Ok(GitOutput::Ok("remote.origin.url\nurl\0".into())));
+
+            let actual_repositories = GitView::known_repositories(&mock);
+
+            assert_eq!(actual_repositories.unwrap(), Vec::<PathBuf>::new());
+        }
+    }
+
+    mod resolve_web_url {
+        use test_case::test_case;
+
+        use crate::{
+            error::ErrorType,
+            git::{GitOutput, MockGitTrait},
+            snapshot::RepoSnapshot,
+            GitView, ResolveRef,
+        };
+
+        fn mock_repository() -> MockGitTrait {
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_head()
+                .returning(|| Ok(GitOutput::Ok("false\n\nhash\nrefs/heads/main".into())));
+            mock.expect_get_config_list().returning(|| {
+                Ok(GitOutput::Ok(
+                    "remote.origin.url\ngit@github.com:sgoudham/git-view.git\0remote.lab.url\nhttps://gitlab.com/sgoudham/git-view.git\0".into(),
+                ))
+            });
+            mock.expect_get_refs().returning(|| {
+                Ok(GitOutput::Ok(
+                    "refs/remotes/origin/main\0hash\0\0\nrefs/remotes/origin/feature/resolve\0hash\0\0\nrefs/tags/v1.0.0\0hash\0\0".into(),
+                ))
+            });
+            mock.expect_get_top_level()
+                .returning(|| Ok(GitOutput::Ok("/home/me/git-view".into())));
+
+            mock
+        }
+
+        #[test_case("https://github.com/sgoudham/git-view/blob/main/src/lib.rs#L10-L20", "/home/me/git-view/src/lib.rs:10" ; "blob with line range")]
+        #[test_case("https://github.com/sgoudham/git-view/blob/feature/resolve/src/lib.rs", "/home/me/git-view/src/lib.rs" ; "branch with slash")]
+        #[test_case("https://github.com/sgoudham/git-view/tree/v1.0.0/src", "/home/me/git-view/src" ; "tree at tag")]
+        #[test_case("https://github.com/sgoudham/git-view/tree/main", "/home/me/git-view" ; "root of repository")]
+        #[test_case("https://github.com/sgoudham/git-view/blob/abc123/my%20file.rs#L5", "/home/me/git-view/my file.rs:5" ; "commit with encoded path")]
+        #[test_case("https://gitlab.com/sgoudham/git-view/-/blob/main/src/lib.rs#L10-20", "/home/me/git-view/src/lib.rs:10" ; "gitlab blob")]
+        fn is_resolved(web_url: &str, expected_path: &str) {
+            let handler = GitView::default();
+            let mock = mock_repository();
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+
+            let actual_path = handler.resolve_web_url(web_url, &snapshot, &mock);
+
+            assert!(actual_path.is_ok());
+            assert_eq!(actual_path.unwrap(), expected_path);
+        }
+
+        #[test]
+        fn is_remote_not_found() {
+            let handler = GitView::default();
+            let mock = mock_repository();
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+
+            let actual_path = handler.resolve_web_url(
+                "https://github.com/sgoudham/other/blob/main/src/lib.rs",
+                &snapshot,
+                &mock,
+            );
+
+            assert!(actual_path.is_err());
+            let error = actual_path.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::MissingGitRemote);
+            assert_eq!(
+                error.to_string(),
+                "Looks like none of your git remotes point at 'github.com/sgoudham/other/blob/main/src/lib.rs'"
+            );
+        }
+
+        #[test]
+        fn is_not_file_url() {
+            let handler = GitView::default();
+            let mock = mock_repository();
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+
+            let actual_path = handler.resolve_web_url(
+                "https://github.com/sgoudham/git-view/issues/1",
+                &snapshot,
+                &mock,
+            );
+
+            assert!(actual_path.is_err());
+            assert_eq!(
+                actual_path.unwrap_err().error_type(),
+                ErrorType::InvalidGitUrl
+            );
+        }
+
+        #[test]
+        fn is_verified_path_missing() {
+            let handler = GitView::builder()
+                .with_resolve_ref(ResolveRef::Verify)
+                .build();
+            let mut mock = mock_repository();
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+            mock.expect_get_tree_listing()
+                .withf(|reference, path| {
+                    reference == "refs/remotes/origin/main" && path == "src/gone.rs"
+                })
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+
+            let actual_path = handler.resolve_web_url(
+                "https://github.com/sgoudham/git-view/blob/main/src/gone.rs",
+                &snapshot,
+                &mock,
+            );
+
+            assert!(actual_path.is_err());
+            let error = actual_path.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::MissingPath);
+            assert_eq!(
+                error.to_string(),
+                "Path 'src/gone.rs' does not exist at 'main'"
+            );
+        }
+
+        #[test]
+        fn is_verified_root() {
+            let handler = GitView::builder()
+                .with_resolve_ref(ResolveRef::Verify)
+                .build();
+            // No expectation is set for the tree listing, so listing the root panics
+            let mut mock = mock_repository();
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+            mock.expect_get_revision()
+                .withf(|revision| revision == "refs/remotes/origin/main")
+                .returning(|_| Ok(GitOutput::Ok("hash".into())));
+
+            let actual_path = handler.resolve_web_url(
+                "https://github.com/sgoudham/git-view/tree/main",
+                &snapshot,
+                &mock,
+            );
+
+            assert!(actual_path.is_ok());
+            assert_eq!(actual_path.unwrap(), "/home/me/git-view");
+        }
+
+        #[test]
+        fn is_checked_out() {
+            let handler = GitView::builder()
+                .with_resolve_ref(ResolveRef::Checkout)
+                .build();
+            let mut mock = mock_repository();
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+            mock.expect_get_revision()
+                .withf(|revision| revision == "v1.0.0")
+                .returning(|_| Ok(GitOutput::Ok("hash".into())));
+            mock.expect_checkout()
+                .withf(|reference| reference == "v1.0.0")
+                .times(1)
+                .returning(|_| Ok(GitOutput::Ok("".into())));
+
+            let actual_path = handler.resolve_web_url(
+                "https://github.com/sgoudham/git-view/blob/v1.0.0/README.md#L3",
+                &snapshot,
+                &mock,
+            );
+
+            assert!(actual_path.is_ok());
+            assert_eq!(actual_path.unwrap(), "/home/me/git-view/README.md:3");
+        }
+
+        #[test_case(ResolveRef::Verify   ; "verify")]
+        #[test_case(ResolveRef::Checkout ; "checkout")]
+        fn is_option_like_ref_rejected(resolve_ref: ResolveRef) {
+            let handler = GitView::builder().with_resolve_ref(resolve_ref).build();
+            // No expectations are set for the tree listing or the checkout, so calling them panics
+            let mock = mock_repository();
+            let snapshot = RepoSnapshot::new(&mock).unwrap();
+
+            let actual_path = handler.resolve_web_url(
+                "https://github.com/sgoudham/git-view/blob/--force/README.md",
+                &snapshot,
+                &mock,
+            );
+
+            assert!(actual_path.is_err());
+            let error = actual_path.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::InvalidRevision);
+            assert_eq!(
+                error.to_string(),
+                "Ref '--force' in url 'https://github.com/sgoudham/git-view/blob/--force/README.md' is not a valid ref"
+            );
+        }
+    }
+
+    mod generate_url {
+        use std::borrow::Cow;
+
+        use test_case::test_case;
+
+        use crate::{
+            git::{Local, MockGitTrait},
+            snapshot::RepoSnapshot,
+            GitView,
+        };
+
+        #[test_case("",                             "https://github.com/sgoudham/git-view/issues/1234"   ; "unpushed")]
+        #[test_case("branch.PROJ-1234-thing.merge\nrefs/heads/main\0", "https://github.com/sgoudham/git-view/issues/1234" ; "tracking other branch")]
+        fn is_issue_from_local_branch(branch_config: &str, expected_url: &str) {
+            let handler = GitView::builder().with_issue("branch").build();
+            let local = Local::Branch(Cow::Borrowed("PROJ-1234-thing"));
+            let snapshot = RepoSnapshot::parse(
+                &format!("remote.origin.url\nhttps://github.com/sgoudham/git-view.git\0view.issuepattern\nPROJ-(\\d+)\0{branch_config}"),
+                "",
+                "refs/remotes/origin/HEAD\0hash\0\0refs/remotes/origin/main\nrefs/remotes/origin/main\0hash\0\0",
+            );
+            let mock = MockGitTrait::default();
+
+            let actual_url = handler.generate_url(&local, "origin", &snapshot, &mock);
+
+            assert_eq!(actual_url.unwrap(), expected_url);
+        }
+
+        #[test]
+        fn is_issue_tracker_key_from_local_branch() {
+            let handler = GitView::builder().with_issue("branch").build();
+            let local = Local::Branch(Cow::Borrowed("PROJ-1234-thing"));
+            let snapshot = RepoSnapshot::parse(
+                "remote.origin.url\nhttps://github.com/sgoudham/git-view.git\0branch.PROJ-1234-thing.merge\nrefs/heads/main\0view.issuetracker\nhttps://jira.example.com/browse/{key}\0",
+                "",
+                "refs/remotes/origin/main\0hash\0\0",
+            );
+            let mock = MockGitTrait::default();
+
+            let actual_url = handler.generate_url(&local, "origin", &snapshot, &mock);
+
+            assert_eq!(
+                actual_url.unwrap(),
+                "https://jira.example.com/browse/PROJ-1234"
+            );
+        }
+    }
+
+    mod get_local_ref {
+        use std::borrow::Cow;
+
+        use mockall::predicate::eq;
+        use test_case::test_case;
+
+        use crate::{
+            git::{GitOutput, MockGitTrait},
+            snapshot::RepoSnapshot,
+            GitView, Local,
+        };
+
+        #[test]
+        fn user_given_branch() {
+            let handler = GitView::builder().with_branch("main").build();
+            let snapshot = RepoSnapshot::default();
+            let mock = MockGitTrait::default();
+            let expected_local_ref = Ok(Local::Branch(Cow::Borrowed("main")));
+
+            let actual_local_ref = handler.get_local_ref(&snapshot, &mock);
+
+            assert!(actual_local_ref.is_ok());
+            assert_eq!(actual_local_ref, expected_local_ref);
+        }
+
+        #[test_case("refs/heads/previous",        "previous"  ; "with local branch")]
+        #[test_case("refs/remotes/origin/main",   "main"      ; "with remote branch")]
+        fn user_given_branch_expression(full_name: &'static str, expected_branch: &str) {
+            let handler = GitView::builder().with_branch("@{-1}").build();
+            let snapshot = RepoSnapshot::default();
+            let mut mock = MockGitTrait::default();
+            let expected_local_ref = Ok(Local::Branch(Cow::Borrowed(expected_branch)));
+
+            mock.expect_get_symbolic_branch()
+                .with(eq("@{-1}"))
+                .returning(move |_| Ok(GitOutput::Ok(full_name.into())));
+
+            let actual_local_ref = handler.get_local_ref(&snapshot, &mock);
+
+            assert!(actual_local_ref.is_ok());
+            assert_eq!(actual_local_ref, expected_local_ref);
+        }
+
+        #[test]
+        fn is_branch() {
+            let handler = GitView::default();
+            let snapshot = RepoSnapshot::parse("", "hash\nrefs/heads/dev", "");
+            let mock = MockGitTrait::default();
+            let expected_local_ref = Ok(Local::Branch(Cow::Borrowed("dev")));
+
+            let actual_local_ref = handler.get_local_ref(&snapshot, &mock);
+
+            assert!(actual_local_ref.is_ok());
+            assert_eq!(actual_local_ref, expected_local_ref);
+        }
+
+        #[test]
+        fn is_not_branch() {
+            let handler = GitView::default();
+            let snapshot = RepoSnapshot::parse("", "hash\nHEAD", "");
+            let mut mock = MockGitTrait::default();
+            let expected_local_ref = Ok(Local::NotBranch);
+
+            mock.expect_get_rebase_head_name()
+                .returning(|| Ok(GitOutput::Err("No rebase in progress".into())));
+
+            let actual_local_ref = handler.get_local_ref(&snapshot, &mock);
+
+            assert!(actual_local_ref.is_ok());
+            assert_eq!(actual_local_ref, expected_local_ref);
+        }
+
+        #[test_case("refs/heads/feature",   Local::Branch(Cow::Borrowed("feature")) ; "with branch")]
+        #[test_case("detached HEAD",        Local::NotBranch                        ; "with detached head")]
+        fn is_rebasing(head_name: &'static str, expected_local_ref: Local) {
+            let handler = GitView::default();
+            let snapshot = RepoSnapshot::parse("", "hash\nHEAD", "");
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_rebase_head_name()
+                .returning(move || Ok(GitOutput::Ok(head_name.into())));
+
+            let actual_local_ref = handler.get_local_ref(&snapshot, &mock);
+
+            assert_eq!(actual_local_ref, Ok(expected_local_ref));
+        }
+    }
+
+    mod populate_remote {
+        use std::borrow::Cow;
+
+        use test_case::test_case;
+
+        use crate::{error::ErrorType, snapshot::RepoSnapshot, GitView, Local};
+
+        #[test]
+        fn is_not_branch() {
+            let handler = GitView::builder().with_remote("origin").build();
+            let snapshot = RepoSnapshot::default();
+
+            let actual_remote = handler.populate_remote(&Local::NotBranch, &snapshot);
+
+            assert_eq!(actual_remote.unwrap(), "origin");
+        }
+
+        #[test]
+        fn user_given_remote() {
+            let handler = GitView::builder().with_remote("origin").build();
+            let snapshot = RepoSnapshot::parse("open.default.remote\ndefault_remote\0", "", "");
+
+            let actual_remote =
+                handler.populate_remote(&Local::Branch(Cow::Borrowed("")), &snapshot);
+
+            assert_eq!(actual_remote.unwrap(), handler.remote.unwrap());
+        }
+
+        #[test]
+        fn is_default_remote() {
+            let handler = GitView::default();
+            let snapshot = RepoSnapshot::parse(
+                "open.default.remote\ndefault_remote\0branch.branch.pushremote\npush_remote\0",
+                "",
+                "",
+            );
+
+            let actual_remote =
+                handler.populate_remote(&Local::Branch(Cow::Borrowed("branch")), &snapshot);
+
+            assert_eq!(actual_remote.unwrap(), "default_remote");
+        }
+
+        #[test]
+        fn is_tracked_remote() {
+            let handler = GitView::default();
+            let snapshot = RepoSnapshot::parse("branch.branch.remote\ntracked_remote\0", "", "");
+
+            let actual_remote =
+                handler.populate_remote(&Local::Branch(Cow::Borrowed("branch")), &snapshot);
+
+            assert_eq!(actual_remote.unwrap(), "tracked_remote");
         }
 
-        Ok(())
-    }
-}
+        #[test]
+        fn is_push_remote() {
+            let handler = GitView::default();
+            let snapshot = RepoSnapshot::parse(
+                "branch.branch.remote\ntracked_remote\0remote.pushdefault\npush_default\0branch.branch.pushremote\npush_remote\0",
+                "",
+                "",
+            );
 
-fn capture_digits(remote_ref: &str) -> Option<&str> {
-    let mut start = 0;
-    let mut end = 0;
-    let mut found = false;
+            let actual_remote =
+                handler.populate_remote(&Local::Branch(Cow::Borrowed("branch")), &snapshot);
 
-    for (indice, char) in remote_ref.char_indices() {
-        if found {
-            if char.is_numeric() {
-                end = indice;
-            } else {
-                break;
-            }
-        } else if char.is_numeric() {
-            start = indice;
-            found = true;
+            assert_eq!(actual_remote.unwrap(), "push_remote");
         }
-    }
-
-    if found {
-        Some(&remote_ref[start..=end])
-    } else {
-        None
-    }
-}
 
-fn escape_ascii_chars(remote_ref: &str) -> Cow<'_, str> {
-    // I could use this below but I wanted to be more comfortable with Cow
-    // branch.replace('%', "%25").replace('#', "%23");
+        #[test]
+        fn is_push_default() {
+            let handler = GitView::default();
+            let snapshot = RepoSnapshot::parse(
+                "branch.branch.remote\ntracked_remote\0remote.pushdefault\npush_default\0",
+                "",
+                "",
+            );
 
-    if remote_ref.contains(['%', '#']) {
-        let mut escaped_str = String::with_capacity(remote_ref.len());
+            let actual_remote =
+                handler.populate_remote(&Local::Branch(Cow::Borrowed("branch")), &snapshot);
 
-        for char in remote_ref.chars() {
-            match char {
-                '%' => escaped_str.push_str("%25"),
-                '#' => escaped_str.push_str("%23"),
-                _ => escaped_str.push(char),
-            };
+            assert_eq!(actual_remote.unwrap(), "push_default");
         }
 
-        Cow::Owned(escaped_str)
-    } else {
-        Cow::Borrowed(remote_ref)
-    }
-}
+        #[test]
+        fn is_tracked_remote_for_issue() {
+            let handler = GitView::builder().with_issue("branch").build();
+            let snapshot = RepoSnapshot::parse(
+                "branch.branch.remote\nupstream\0branch.branch.pushremote\npush_remote\0",
+                "",
+                "",
+            );
 
-#[cfg(test)]
-mod lib_tests {
-    use crate::GitView;
+            let actual_remote =
+                handler.populate_remote(&Local::Branch(Cow::Borrowed("branch")), &snapshot);
 
-    impl<'a> GitView<'a> {
-        fn builder() -> GitViewBuilder<'a> {
-            GitViewBuilder::default()
+            assert_eq!(actual_remote.unwrap(), "upstream");
         }
-    }
 
-    #[derive(Default)]
-    pub(crate) struct GitViewBuilder<'a> {
-        remote: Option<&'a str>,
-        branch: Option<&'a str>,
-        commit: Option<&'a str>,
-        issue: Option<&'a str>,
-        path: Option<&'a str>,
-        is_print: bool,
-    }
+        #[test]
+        fn is_not_default_or_tracked() {
+            let handler = GitView::default();
+            let snapshot = RepoSnapshot::default();
 
-    impl<'a> GitViewBuilder<'a> {
-        pub(crate) fn with_remote(mut self, remote: &'a str) -> Self {
-            self.remote = Some(remote);
-            self
-        }
+            let actual_remote =
+                handler.populate_remote(&Local::Branch(Cow::Borrowed("branch")), &snapshot);
 
-        pub(crate) fn with_branch(mut self, branch: &'a str) -> Self {
-            self.branch = Some(branch);
-            self
+            assert_eq!(actual_remote.unwrap(), "origin");
         }
 
-        pub(crate) fn with_commit(mut self, commit: &'a str) -> Self {
-            self.commit = Some(commit);
-            self
+        #[test_case("remote.github.url\nurl\0",                                          "github" ; "with only remote")]
+        #[test_case("remote.github.url\nurl\0remote.origin.url\nurl\0",                  "origin" ; "with origin")]
+        #[test_case("remote.github.url\nurl\0remote.gitlab.url\nurl\0view.remotepriority\nbitbucket, gitlab, github\0", "gitlab" ; "with remote priority")]
+        fn is_selected_remote(config: &str, expected_remote: &str) {
+            let handler = GitView::default();
+            let snapshot = RepoSnapshot::parse(config, "", "");
+
+            let actual_remote = handler.populate_remote(&Local::NotBranch, &snapshot);
+
+            assert_eq!(actual_remote.unwrap(), expected_remote);
         }
 
-        pub(crate) fn with_issue(mut self, issue: &'a str) -> Self {
-            self.issue = Some(issue);
-            self
+        #[test_case("remote.upstream.url\nurl\0",                                   "upstream" ; "with upstream remote")]
+        #[test_case("remote.upstream.url\nurl\0view.upstreamremote\nparent\0",    "parent"   ; "with upstream remote in config")]
+        fn is_upstream(config: &str, expected_remote: &str) {
+            let handler = GitView::builder().with_upstream(true).build();
+            let snapshot = RepoSnapshot::parse(config, "", "");
+
+            let actual_remote =
+                handler.populate_remote(&Local::Branch(Cow::Borrowed("branch")), &snapshot);
+
+            assert_eq!(actual_remote.unwrap(), expected_remote);
         }
 
-        pub(crate) fn with_path(mut self, path: &'a str) -> Self {
-            self.path = Some(path);
-            self
+        #[test]
+        fn is_missing_upstream() {
+            let handler = GitView::builder().with_upstream(true).build();
+            let snapshot = RepoSnapshot::parse("remote.origin.url\nurl\0", "", "");
+
+            let actual_remote = handler.populate_remote(&Local::NotBranch, &snapshot);
+
+            assert!(actual_remote.is_err());
+            assert_eq!(
+                actual_remote.unwrap_err().error_type(),
+                ErrorType::MissingGitRemote
+            );
         }
 
-        pub(crate) fn build(self) -> GitView<'a> {
-            GitView::new(
-                self.branch,
-                self.remote,
-                self.commit,
-                self.issue,
-                self.path,
-                self.is_print,
-            )
+        #[test]
+        fn is_ambiguous_remote() {
+            let handler = GitView::default();
+            let snapshot =
+                RepoSnapshot::parse("remote.github.url\nurl\0remote.gitlab.url\nurl\0", "", "");
+
+            let actual_remote = handler.populate_remote(&Local::NotBranch, &snapshot);
+
+            assert!(actual_remote.is_err());
+            let error = actual_remote.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::MissingGitRemote);
+            assert_eq!(
+                error.to_string(),
+                "Looks like there's no 'origin' remote, choose one of 'github', 'gitlab' with --remote or set 'view.remotePriority'"
+            );
         }
     }
 
-    mod is_valid_repository {
+    mod get_remote_reference {
+        use std::borrow::Cow;
+
+        use mockall::predicate::eq;
+        use test_case::test_case;
+
         use crate::{
             error::ErrorType,
             git::{GitOutput, MockGitTrait},
-            GitView,
+            snapshot::RepoSnapshot,
+            GitView, Local, Unpushed,
         };
 
         #[test]
-        fn yes() {
+        fn is_branch_and_exists_on_remote() {
             let handler = GitView::default();
-            let mut mock = MockGitTrait::default();
-
-            mock.expect_is_valid_repository()
-                .returning(|| Ok(GitOutput::Ok("Valid".to_owned())));
+            let local = Local::Branch(Cow::Borrowed("main"));
+            let snapshot = RepoSnapshot::parse(
+                "branch.main.remote\norigin\0branch.main.merge\nrefs/heads/main\0",
+                "",
+                "refs/remotes/origin/main\0hash\0\0",
+            );
+            let mock = MockGitTrait::default();
 
-            let is_valid_repository = handler.is_valid_repository(&mock);
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            assert!(is_valid_repository.is_ok());
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "main");
         }
 
         #[test]
-        fn no() {
+        fn is_branch_in_bare_repository() {
             let handler = GitView::default();
-            let mut mock = MockGitTrait::default();
+            let local = Local::Branch(Cow::Borrowed("main"));
+            let snapshot = RepoSnapshot::parse("", "true\n\nhash\nrefs/heads/main", "");
+            let mock = MockGitTrait::default();
 
-            mock.expect_is_valid_repository()
-                .returning(|| Ok(GitOutput::Err("Error".to_owned())));
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            let is_valid_repository = handler.is_valid_repository(&mock);
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "main");
+        }
 
-            assert!(is_valid_repository.is_err());
-            let error = is_valid_repository.as_ref().unwrap_err();
-            assert_eq!(error.error_type, ErrorType::MissingGitRepository);
-            assert_eq!(
-                error.error_str,
-                "Looks like you're not in a valid git repository!"
+        #[test]
+        fn is_branch_and_upstream_has_different_name() {
+            let handler = GitView::default();
+            let local = Local::Branch(Cow::Borrowed("feature"));
+            let snapshot = RepoSnapshot::parse(
+                "branch.feature.remote\norigin\0branch.feature.merge\nrefs/heads/user/feature\0",
+                "",
+                "refs/remotes/origin/user/feature\0hash\0\0",
             );
-        }
-    }
+            let mock = MockGitTrait::default();
 
-    mod get_local_ref {
-        use std::borrow::Cow;
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-        use crate::{
-            git::{GitOutput, MockGitTrait},
-            GitView, Local,
-        };
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "user/feature");
+        }
 
         #[test]
-        fn user_given_branch() {
-            let handler = GitView::builder().with_branch("main").build();
+        fn is_branch_and_upstream_deleted_from_remote() {
+            let handler = GitView::default();
+            let local = Local::Branch(Cow::Borrowed("feature"));
+            let snapshot = RepoSnapshot::parse(
+                "branch.feature.remote\norigin\0branch.feature.merge\nrefs/heads/deleted\0",
+                "",
+                "refs/remotes/origin/feature\0hash\0\0",
+            );
             let mock = MockGitTrait::default();
-            let expected_local_ref = Ok(Local::Branch(Cow::Borrowed("main")));
 
-            let actual_local_ref = handler.get_local_ref(&mock);
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            assert!(actual_local_ref.is_ok());
-            assert_eq!(actual_local_ref, expected_local_ref);
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "feature");
         }
 
         #[test]
-        fn is_branch() {
+        fn is_branch_and_pushed_without_upstream() {
             let handler = GitView::default();
-            let mut mock = MockGitTrait::default();
-            let expected_local_ref = Ok(Local::Branch(Cow::Borrowed("dev")));
-
-            mock.expect_get_local_branch()
-                .returning(|| Ok(GitOutput::Ok("dev".into())));
+            let local = Local::Branch(Cow::Borrowed("feature"));
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/origin/feature\0hash\0\0");
+            let mock = MockGitTrait::default();
 
-            let actual_local_ref = handler.get_local_ref(&mock);
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            assert!(actual_local_ref.is_ok());
-            assert_eq!(actual_local_ref, expected_local_ref);
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "feature");
         }
 
         #[test]
-        fn is_not_branch() {
+        fn is_branch_and_successfully_get_default() {
             let handler = GitView::default();
-            let mut mock = MockGitTrait::default();
-            let expected_local_ref = Ok(Local::NotBranch);
+            let local = Local::Branch(Cow::Borrowed("testing"));
+            let snapshot = RepoSnapshot::parse(
+                "branch.testing.remote\norigin\0",
+                "",
+                "refs/remotes/origin/HEAD\0hash\0\0refs/remotes/origin/main",
+            );
+            let mock = MockGitTrait::default();
 
-            mock.expect_get_local_branch()
-                .returning(|| Ok(GitOutput::Err("Error".into())));
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            let actual_local_ref = handler.get_local_ref(&mock);
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "main")
+        }
 
-            assert!(actual_local_ref.is_ok());
-            assert_eq!(actual_local_ref, expected_local_ref);
+        #[test]
+        fn is_branch_and_get_default_without_remote_head() {
+            let handler = GitView::default();
+            let local = Local::Branch(Cow::Borrowed("feature"));
+            let snapshot = RepoSnapshot::parse(
+                "branch.feature.remote\norigin\0",
+                "",
+                "refs/remotes/upstream/master\0hash\0\0",
+            );
+            let mock = MockGitTrait::default();
+
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "upstream", &snapshot, &mock);
+
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "master");
         }
-    }
 
-    mod populate_remote {
-        use std::borrow::Cow;
+        #[test]
+        fn is_branch_and_fail_to_get_default() {
+            let handler = GitView::default();
+            let local = Local::Branch(Cow::Borrowed("testing"));
+            let snapshot = RepoSnapshot::parse("branch.testing.remote\norigin\0", "", "");
+            let mock = MockGitTrait::default();
 
-        use mockall::predicate::eq;
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-        use crate::{
-            git::{GitOutput, MockGitTrait},
-            GitView, Local,
-        };
+            assert!(actual_upstream_branch.is_err());
+            assert_eq!(
+                actual_upstream_branch.unwrap_err().to_string(),
+                "Could not verify 'origin/testing' exists and could not retrieve default branch\nSet the default branch with 'git remote set-head origin --auto'"
+            );
+        }
 
         #[test]
-        fn is_not_branch() {
-            let handler = GitView::builder().with_remote("origin").build();
+        fn is_user_branch_and_suggested() {
+            let handler = GitView::builder().with_branch("feture").build();
+            let local = Local::Branch(Cow::Borrowed("feture"));
+            let snapshot = RepoSnapshot::parse(
+                "",
+                "",
+                "refs/heads/main\0hash\0\0\nrefs/remotes/origin/feature\0hash\0\0",
+            );
             let mock = MockGitTrait::default();
 
-            let actual_remote = handler.populate_remote(&Local::NotBranch, &mock);
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            assert!(actual_remote.is_ok());
-            assert_eq!(actual_remote.unwrap(), "origin");
+            assert!(actual_upstream_branch.is_err());
+            assert_eq!(
+                actual_upstream_branch.unwrap_err().to_string(),
+                "Could not verify 'origin/feture' exists and could not retrieve default branch, did you mean 'feature'?\nView it with '--branch feature'\nSet the default branch with 'git remote set-head origin --auto'"
+            );
         }
 
         #[test]
-        fn user_given_remote() {
-            let handler = GitView::builder().with_remote("origin").build();
+        fn is_branch_and_pushed_to_other_remote() {
+            let handler = GitView::default();
+            let local = Local::Branch(Cow::Borrowed("feature"));
+            let snapshot = RepoSnapshot::parse(
+                "branch.feature.remote\nupstream\0branch.feature.merge\nrefs/heads/main\0",
+                "",
+                "refs/remotes/origin/main\0hash\0\0\nrefs/remotes/origin/feature\0hash\0\0",
+            );
             let mock = MockGitTrait::default();
 
-            let actual_remote = handler.populate_remote(&Local::Branch(Cow::Borrowed("")), &mock);
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            assert!(actual_remote.is_ok());
-            assert_eq!(actual_remote.unwrap(), handler.remote.unwrap());
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "feature");
         }
 
         #[test]
-        fn is_default_remote() {
+        fn not_branch_and_get_current_tag() {
+            let handler = GitView::default();
+            let local = Local::NotBranch;
+            let snapshot = RepoSnapshot::parse("", "hash\nHEAD", "refs/tags/v1.0.0\0hash\0\0");
+            let mock = MockGitTrait::default();
+
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
+
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "v1.0.0")
+        }
+
+        #[test_case("origin/HEAD -> origin/main\n  origin/feature\n  origin/main", "main"    ; "with default branch")]
+        #[test_case("origin/feature\n  origin/fix",                               "feature" ; "with other branch")]
+        fn not_branch_and_get_remote_branch(branches: &'static str, expected_branch: &str) {
             let handler = GitView::default();
+            let local = Local::NotBranch;
+            let snapshot = RepoSnapshot::parse(
+                "",
+                "hash\nHEAD",
+                "refs/tags/v1.0.0\0other\0\0\nrefs/remotes/origin/HEAD\0hash\0\0refs/remotes/origin/main",
+            );
             let mut mock = MockGitTrait::default();
 
-            mock.expect_get_default_remote()
-                .returning(|| Ok(GitOutput::Ok("default_remote".into())));
+            mock.expect_get_remote_branches_containing()
+                .with(eq("origin"), eq("hash"))
+                .returning(move |_, _| Ok(GitOutput::Ok(branches.into())));
 
-            let actual_remote = handler.populate_remote(&Local::Branch(Cow::Borrowed("")), &mock);
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            assert!(actual_remote.is_ok());
-            assert_eq!(actual_remote.unwrap(), "default_remote");
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), expected_branch)
         }
 
         #[test]
-        fn is_tracked_remote() {
+        fn not_branch_in_bare_repository() {
             let handler = GitView::default();
+            let local = Local::NotBranch;
+            let snapshot = RepoSnapshot::parse("", "true\n\nhash\nHEAD", "");
+            let mock = MockGitTrait::default();
+
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
+
+            assert!(actual_upstream_branch.is_ok());
+            assert_eq!(actual_upstream_branch.unwrap(), "hash")
+        }
+
+        #[test_case(Unpushed::Warn,     Ok("hash".into())       ; "with warning")]
+        #[test_case(Unpushed::Ancestor, Ok("ancestor".into())   ; "with pushed ancestor")]
+        #[test_case(Unpushed::Refuse,   Err(ErrorType::UnpushedCommit) ; "with refusal")]
+        fn not_branch_and_get_unpushed_commit(
+            unpushed: Unpushed,
+            expected_remote_ref: Result<String, ErrorType>,
+        ) {
+            let handler = GitView::builder().with_unpushed(unpushed).build();
+            let local = Local::NotBranch;
+            let snapshot = RepoSnapshot::parse("", "hash\nHEAD", "");
             let mut mock = MockGitTrait::default();
 
-            mock.expect_get_default_remote()
-                .returning(|| Ok(GitOutput::Err("error".into())));
-            mock.expect_get_tracked_remote()
-                .with(eq("branch"))
-                .returning(|_| Ok(GitOutput::Ok("tracked_remote".into())));
+            mock.expect_get_remote_branches_containing()
+                .with(eq("origin"), eq("hash"))
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+            mock.expect_get_pushed_boundary()
+                .with(eq("origin"), eq("hash"))
+                .returning(|_, _| Ok(GitOutput::Ok("hash\nparent\n-ancestor".into())));
 
-            let actual_remote =
-                handler.populate_remote(&Local::Branch(Cow::Borrowed("branch")), &mock);
+            let actual_remote_ref =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            assert!(actual_remote.is_ok());
-            assert_eq!(actual_remote.unwrap(), "tracked_remote");
+            assert_eq!(
+                actual_remote_ref
+                    .map(|remote_ref| remote_ref.into_owned())
+                    .map_err(|error| error.error_type()),
+                expected_remote_ref
+            );
         }
 
         #[test]
-        fn is_not_default_or_tracked() {
+        fn not_branch_and_no_tag_or_commit() {
             let handler = GitView::default();
-            let mut mock = MockGitTrait::default();
+            let local = Local::NotBranch;
+            let snapshot = RepoSnapshot::default();
+            let mock = MockGitTrait::default();
 
-            mock.expect_get_default_remote()
-                .returning(|| Ok(GitOutput::Err("error".into())));
-            mock.expect_get_tracked_remote()
-                .with(eq("branch"))
-                .returning(|_| Ok(GitOutput::Err("error".into())));
+            let actual_upstream_branch =
+                handler.get_remote_reference(&local, "origin", &snapshot, &mock);
 
-            let actual_remote =
-                handler.populate_remote(&Local::Branch(Cow::Borrowed("branch")), &mock);
+            assert!(actual_upstream_branch.is_err());
 
-            assert!(actual_remote.is_ok());
-            assert_eq!(actual_remote.unwrap(), "origin");
+            let error = actual_upstream_branch.as_ref().unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::CommandFailed);
+            assert_eq!(error.to_string(), "Could not find the current commit");
         }
     }
 
-    mod get_remote_reference {
-        use std::borrow::Cow;
-
-        use crate::{
-            error::ErrorType,
-            git::{GitOutput, MockGitTrait},
-            GitView, Local,
-        };
+    mod get_git_url {
+        use crate::{snapshot::RepoSnapshot, GitView};
 
         #[test]
-        fn is_branch_and_exists_on_remote() {
+        fn is_valid_remote() {
             let handler = GitView::default();
-            let local = Local::Branch(Cow::Borrowed("main"));
-            let mut mock = MockGitTrait::default();
-
-            mock.expect_get_upstream_branch()
-                .returning(|_| Ok(GitOutput::Ok("refs/heads/main".into())));
+            let snapshot = RepoSnapshot::parse(
+                "remote.origin.url\nhttps://github.com/sgoudham/git-view\0",
+                "",
+                "",
+            );
 
-            let actual_upstream_branch = handler.get_remote_reference(&local, "origin", &mock);
+            let actual_remote = handler.get_git_url("origin", &snapshot);
 
-            assert!(actual_upstream_branch.is_ok());
-            assert_eq!(actual_upstream_branch.unwrap(), "main");
+            assert!(actual_remote.is_ok());
+            assert_eq!(
+                actual_remote.unwrap(),
+                "https://github.com/sgoudham/git-view"
+            )
         }
 
         #[test]
-        fn is_branch_and_successfully_get_default() {
+        fn is_not_valid_remote() {
             let handler = GitView::default();
-            let local = Local::Branch(Cow::Borrowed("main"));
-            let mut mock = MockGitTrait::default();
-
-            mock.expect_get_upstream_branch()
-                .returning(|_| Ok(GitOutput::Err("error".into())));
-            mock.expect_get_default_branch()
-                .returning(|_| Ok(GitOutput::Ok("origin/main".into())));
+            let snapshot = RepoSnapshot::parse(
+                "remote.upstream.url\nhttps://github.com/sgoudham/git-view\0",
+                "",
+                "",
+            );
 
-            let actual_upstream_branch = handler.get_remote_reference(&local, "origin", &mock);
+            let actual_remote = handler.get_git_url("origin", &snapshot);
 
-            assert!(actual_upstream_branch.is_ok());
-            assert_eq!(actual_upstream_branch.unwrap(), "main")
+            assert!(actual_remote.is_err());
+            assert_eq!(
+                actual_remote.unwrap_err().to_string(),
+                "Looks like your git remote isn't set for 'origin'\nSet it with 'git config remote.origin.url <url>'"
+            );
         }
 
         #[test]
-        fn is_branch_and_fail_to_get_default() {
+        fn is_not_valid_remote_and_suggested() {
             let handler = GitView::default();
-            let local = Local::Branch(Cow::Borrowed("testing"));
-            let mut mock = MockGitTrait::default();
-
-            mock.expect_get_upstream_branch()
-                .returning(|_| Ok(GitOutput::Err("error".into())));
-            mock.expect_get_default_branch()
-                .returning(|_| Ok(GitOutput::Err("error".into())));
+            let snapshot = RepoSnapshot::parse(
+                "remote.origin.url\nhttps://github.com/sgoudham/git-view\0remote.upstream.url\nhttps://github.com/other/git-view\0",
+                "",
+                "",
+            );
 
-            let actual_upstream_branch = handler.get_remote_reference(&local, "origin", &mock);
+            let actual_remote = handler.get_git_url("upstrem", &snapshot);
 
-            assert!(actual_upstream_branch.is_err());
+            assert!(actual_remote.is_err());
             assert_eq!(
-                actual_upstream_branch.unwrap_err().error_str,
-                "Could not verify 'origin/testing' exists and could not retrieve default branch"
+                actual_remote.unwrap_err().to_string(),
+                "Looks like your git remote isn't set for 'upstrem', did you mean 'upstream'?\nView it with '--remote upstream' or make it the default with 'git config open.default.remote upstream'"
             );
         }
+    }
+
+    mod get_submodule {
+        use mockall::predicate::eq;
+
+        use crate::{
+            git::{GitOutput, MockGitTrait},
+            snapshot::RepoSnapshot,
+            GitView, Submodule,
+        };
+
+        const GITMODULES: &str =
+            "submodule.libs/lib.path\nlibs/lib\0submodule.libs/lib.url\n../lib.git\0";
+        const GIT_URL: &str = "git@github.com:sgoudham/git-view.git";
 
         #[test]
-        fn not_branch_and_get_current_tag() {
-            let handler = GitView::default();
-            let local = Local::NotBranch;
+        fn is_not_in_submodule() {
+            let handler = GitView::builder().with_path("libs/other").build();
+            let snapshot = RepoSnapshot::default();
             let mut mock = MockGitTrait::default();
 
-            mock.expect_get_current_tag()
-                .returning(|| Ok(GitOutput::Ok("v1.0.0".into())));
+            mock.expect_get_submodules()
+                .returning(|| Ok(GitOutput::Ok(GITMODULES.into())));
 
-            let actual_upstream_branch = handler.get_remote_reference(&local, "origin", &mock);
+            let actual_submodule = handler.get_submodule(GIT_URL, &snapshot, &mock);
 
-            assert!(actual_upstream_branch.is_ok());
-            assert_eq!(actual_upstream_branch.unwrap(), "v1.0.0")
+            assert_eq!(actual_submodule, Ok(None));
         }
 
         #[test]
-        fn not_branch_and_get_current_commit() {
-            let handler = GitView::default();
-            let local = Local::NotBranch;
+        fn is_without_submodules() {
+            let handler = GitView::builder().with_path("libs/lib").build();
+            let snapshot = RepoSnapshot::default();
             let mut mock = MockGitTrait::default();
 
-            mock.expect_get_current_tag()
-                .returning(|| Ok(GitOutput::Err("error".into())));
-            mock.expect_get_current_commit()
-                .returning(|| Ok(GitOutput::Ok("hash".into())));
+            mock.expect_get_submodules()
+                .returning(|| Ok(GitOutput::Err("".into())));
 
-            let actual_upstream_branch = handler.get_remote_reference(&local, "origin", &mock);
+            let actual_submodule = handler.get_submodule(GIT_URL, &snapshot, &mock);
 
-            assert!(actual_upstream_branch.is_ok());
-            assert_eq!(actual_upstream_branch.unwrap(), "hash")
+            assert_eq!(actual_submodule, Ok(None));
         }
 
         #[test]
-        fn not_branch_and_no_tag_or_commit() {
-            let handler = GitView::default();
-            let local = Local::NotBranch;
+        fn is_checked_out_branch() {
+            let handler = GitView::builder().with_path("lib/src/").build();
+            let snapshot = RepoSnapshot::parse(
+                "submodule.libs/lib.url\nhttps://github.com/other/lib.git\0",
+                "libs/\nhash\nrefs/heads/main",
+                "",
+            );
             let mut mock = MockGitTrait::default();
 
-            mock.expect_get_current_tag()
-                .returning(|| Ok(GitOutput::Err("error".into())));
-            mock.expect_get_current_commit()
-                .returning(|| Ok(GitOutput::Err("error".into())));
-
-            let actual_upstream_branch = handler.get_remote_reference(&local, "origin", &mock);
+            mock.expect_get_submodules()
+                .returning(|| Ok(GitOutput::Ok(GITMODULES.into())));
+            mock.expect_get_top_level()
+                .returning(|| Ok(GitOutput::Ok("/home/me/git-view".into())));
+            mock.expect_get_submodule_branch()
+                .with(eq("/home/me/git-view/libs/lib"))
+                .returning(|_| Ok(GitOutput::Ok("develop".into())));
 
-            assert!(actual_upstream_branch.is_err());
+            let actual_submodule = handler.get_submodule(GIT_URL, &snapshot, &mock);
 
-            let error = actual_upstream_branch.as_ref().unwrap_err();
-            assert_eq!(error.error_type, ErrorType::CommandFailed);
-            assert_eq!(error.error_str, "error");
+            assert_eq!(
+                actual_submodule,
+                Ok(Some(Submodule {
+                    url: "https://github.com/other/lib.git".into(),
+                    reference: "develop".into(),
+                    path: "src/".into(),
+                }))
+            );
         }
-    }
-
-    mod get_git_url {
-        use crate::{
-            error::{AppError, ErrorType},
-            git::{GitOutput, MockGitTrait},
-            GitView,
-        };
 
         #[test]
-        fn is_valid_remote() {
+        fn is_pinned_commit() {
             let handler = GitView::default();
-            let expected_remote = "origin";
+            let snapshot = RepoSnapshot::parse("", "libs/lib/\nhash\nrefs/heads/main", "");
             let mut mock = MockGitTrait::default();
 
-            mock.expect_is_valid_remote()
-                .returning(|_| Ok(GitOutput::Ok("https://github.com/sgoudham/git-view".into())));
+            mock.expect_get_submodules()
+                .returning(|| Ok(GitOutput::Ok(GITMODULES.into())));
+            mock.expect_get_top_level()
+                .returning(|| Ok(GitOutput::Ok("/home/me/git-view".into())));
+            mock.expect_get_submodule_branch()
+                .returning(|_| Ok(GitOutput::Err("".into())));
+            mock.expect_get_tree_entry()
+                .with(eq("HEAD"), eq("libs/lib"))
+                .returning(|_, _| Ok(GitOutput::Ok("gitlink".into())));
 
-            let actual_remote = handler.get_git_url(expected_remote, &mock);
+            let actual_submodule = handler.get_submodule(GIT_URL, &snapshot, &mock);
 
-            assert!(actual_remote.is_ok());
             assert_eq!(
-                actual_remote.unwrap(),
-                "https://github.com/sgoudham/git-view"
-            )
+                actual_submodule,
+                Ok(Some(Submodule {
+                    url: "git@github.com:sgoudham/lib.git".into(),
+                    reference: "gitlink".into(),
+                    path: "".into(),
+                }))
+            );
         }
 
         #[test]
-        fn is_not_valid_remote() {
-            let handler = GitView::default();
-            let expected_remote = "origin";
+        fn is_pinned_commit_of_user_commit() {
+            let handler = GitView::builder()
+                .with_commit("v1.0.0")
+                .with_path("libs/lib/README.md")
+                .build();
+            let snapshot = RepoSnapshot::default();
             let mut mock = MockGitTrait::default();
 
-            mock.expect_is_valid_remote()
-                .returning(|_| Ok(GitOutput::Ok("origin".into())));
+            mock.expect_get_submodules()
+                .returning(|| Ok(GitOutput::Ok(GITMODULES.into())));
+            mock.expect_get_top_level()
+                .returning(|| Ok(GitOutput::Ok("/home/me/git-view".into())));
+            mock.expect_get_revision()
+                .with(eq("v1.0.0"))
+                .returning(|_| Ok(GitOutput::Ok("eafdb9a".into())));
+            mock.expect_get_tree_entry()
+                .with(eq("eafdb9a"), eq("libs/lib"))
+                .returning(|_, _| Ok(GitOutput::Ok("gitlink".into())));
 
-            let actual_remote = handler.get_git_url(expected_remote, &mock);
+            let actual_submodule = handler.get_submodule(GIT_URL, &snapshot, &mock);
 
-            assert!(actual_remote.is_err());
             assert_eq!(
-                actual_remote.unwrap_err().error_str,
-                "Looks like your git remote isn't set for 'origin'"
+                actual_submodule,
+                Ok(Some(Submodule {
+                    url: "git@github.com:sgoudham/lib.git".into(),
+                    reference: "gitlink".into(),
+                    path: "README.md".into(),
+                }))
             );
         }
+    }
 
-        #[test]
-        fn command_failed() {
-            let handler = GitView::default();
-            let expected_remote = "origin";
-            let mut mock = MockGitTrait::default();
+    mod suggest {
+        use test_case::test_case;
 
-            mock.expect_is_valid_remote()
-                .returning(|_| Err(AppError::new(ErrorType::CommandFailed, "error".into())));
+        use crate::{edit_distance, suggest};
 
-            let actual_remote = handler.get_git_url(expected_remote, &mock);
+        #[test_case("upstream", "upstream", 0 ; "equal")]
+        #[test_case("upstrem",  "upstream", 1 ; "insertion")]
+        #[test_case("origin",   "orign",    1 ; "deletion")]
+        #[test_case("main",     "mian",     1 ; "transposition")]
+        #[test_case("feature",  "faeture",  1 ; "transposition within")]
+        #[test_case("",         "main",     4 ; "empty")]
+        fn distance(a: &str, b: &str, expected_distance: usize) {
+            assert_eq!(edit_distance(a, b), expected_distance);
+        }
 
-            assert!(actual_remote.is_err());
-            assert_eq!(actual_remote.unwrap_err().error_str, "error");
+        #[test_case("upstrem", &["origin", "upstream"], Some("upstream") ; "closest")]
+        #[test_case("origin",  &["origin", "upstream"], None             ; "exact match")]
+        #[test_case("fork",    &["origin", "upstream"], None             ; "too far")]
+        fn suggestion(
+            target: &str,
+            candidates: &[&'static str],
+            expected_suggestion: Option<&str>,
+        ) {
+            assert_eq!(
+                suggest(target, candidates.iter().copied()),
+                expected_suggestion
+            );
+        }
+    }
+
+    mod resolve_relative_url {
+        use test_case::test_case;
+
+        use crate::resolve_relative_url;
+
+        #[test_case("https://github.com/sgoudham/git-view.git",  "../lib.git",          "https://github.com/sgoudham/lib.git"    ; "with sibling")]
+        #[test_case("https://github.com/sgoudham/git-view/",     "../../other/lib",     "https://github.com/other/lib"           ; "with other owner")]
+        #[test_case("https://github.com/sgoudham/git-view",      "./lib",               "https://github.com/sgoudham/git-view/lib" ; "with child")]
+        #[test_case("git@github.com:sgoudham/git-view.git",      "../lib.git",          "git@github.com:sgoudham/lib.git"        ; "with scp-like sibling")]
+        #[test_case("git@github.com:sgoudham/git-view.git",      "../../other/lib.git", "git@github.com:other/lib.git"           ; "with scp-like other owner")]
+        fn resolve(base: &str, relative: &str, expected_url: &str) {
+            assert_eq!(resolve_relative_url(base, relative), expected_url);
         }
     }
 
@@ -806,16 +3175,20 @@ mod lib_tests {
 
             assert!(error.is_err());
             assert_eq!(
-                error.unwrap_err().error_str,
+                error.unwrap_err().to_string(),
                 "Sorry, couldn't parse git url 'This isn't a git url'"
             );
         }
     }
 
     mod generate_final_url {
+        use mockall::predicate::eq;
+
         use crate::{
+            error::ErrorType,
             git::{GitOutput, MockGitTrait, Url},
-            GitView,
+            snapshot::RepoSnapshot,
+            GitView, MissingPath,
         };
         use test_case::test_case;
 
@@ -823,13 +3196,14 @@ mod lib_tests {
         fn is_latest_commit() {
             let handler = GitView::builder().with_commit("current").build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "eafdb9a\nrefs/heads/main", "");
             let expected_final_url = "https://github.com/sgoudham/git-view/tree/eafdb9a";
             let mut mock = MockGitTrait::default();
+            mock.expect_get_remote_branches_containing()
+                .returning(|_, _| Ok(GitOutput::Ok("origin/main".into())));
 
-            mock.expect_get_current_commit()
-                .returning(|| Ok(GitOutput::Ok("eafdb9a".into())));
-
-            let actual_final_url = handler.generate_final_url("main", &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
@@ -837,102 +3211,507 @@ mod lib_tests {
 
         #[test]
         fn is_user_commit() {
+            let handler = GitView::builder().with_commit("HEAD~3").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::default();
+            let expected_final_url =
+                "https://github.com/sgoudham/git-view/tree/8s2jl250as7f234jasfjj";
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_revision()
+                .with(eq("HEAD~3"))
+                .returning(|_| Ok(GitOutput::Ok("8s2jl250as7f234jasfjj".into())));
+            mock.expect_get_remote_branches_containing()
+                .returning(|_, _| Ok(GitOutput::Ok("origin/main".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test_case("Fix the thing (#42)",            "",                                          "/pull/42"             ; "with squashed commit")]
+        #[test_case("Merge pull request #42 from x/y", "",                                          "/pull/42"             ; "with merge commit")]
+        #[test_case("Fix the thing",                   "Merge branch 'main'\0\nMerge pull request #42 from x/y\n\nFix\0\nMerge pull request #50 from x/z\0", "/pull/42" ; "with first merge commit")]
+        #[test_case("Merge branch 'fix' into 'main'\n\nFix the thing\n\nSee merge request sgoudham/git-view!42", "", "/-/merge_requests/42" ; "with gitlab merge commit")]
+        #[test_case("Fix the thing",                   "Merge branch 'main'\0\nMerge branch 'fix' into 'main'\n\nSee merge request sgoudham/git-view!42\0", "/-/merge_requests/42" ; "with first gitlab merge commit")]
+        fn is_origin_pr(message: &'static str, merge_messages: &'static str, expected_path: &str) {
+            let handler = GitView::builder()
+                .with_commit("eafdb9a")
+                .with_origin_pr(true)
+                .build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/origin/main\0hash\0\0");
+            let expected_final_url = format!("https://github.com/sgoudham/git-view{expected_path}");
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_revision()
+                .returning(|_| Ok(GitOutput::Ok("eafdb9a".into())));
+            mock.expect_get_commit_message()
+                .with(eq("eafdb9a"))
+                .returning(move |_| Ok(GitOutput::Ok(message.into())));
+            mock.expect_get_merge_messages()
+                .with(eq("eafdb9a"), eq("origin/main"))
+                .returning(move |_, _| Ok(GitOutput::Ok(merge_messages.into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test]
+        fn is_origin_pr_since_default_branch() {
+            let handler = GitView::builder()
+                .with_commit("eafdb9a")
+                .with_origin_pr(true)
+                .build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse(
+                "",
+                "",
+                "refs/remotes/origin/HEAD\0hash\0\0refs/remotes/origin/develop\nrefs/remotes/origin/develop\0hash\0\0",
+            );
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_revision()
+                .returning(|_| Ok(GitOutput::Ok("eafdb9a".into())));
+            mock.expect_get_commit_message()
+                .returning(|_| Ok(GitOutput::Ok("Fix the thing".into())));
+            mock.expect_get_merge_messages()
+                .with(eq("eafdb9a"), eq("origin/develop"))
+                .returning(|_, _| Ok(GitOutput::Ok("Merge pull request #7 from x/y".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(
+                actual_final_url.unwrap(),
+                "https://github.com/sgoudham/git-view/pull/7"
+            );
+        }
+
+        #[test]
+        fn is_origin_pr_not_found() {
+            let handler = GitView::builder()
+                .with_commit("eafdb9a")
+                .with_origin_pr(true)
+                .build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::default();
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_revision()
+                .returning(|_| Ok(GitOutput::Ok("eafdb9a".into())));
+            mock.expect_get_commit_message()
+                .returning(|_| Ok(GitOutput::Ok("Fix the thing".into())));
+            mock.expect_get_merge_messages()
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_err());
+            let error = actual_final_url.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::MissingPullRequest);
+            assert_eq!(
+                error.to_string(),
+                "Could not find the pull request that introduced commit 'eafdb9a'"
+            );
+        }
+
+        #[test]
+        fn is_invalid_user_commit() {
+            let handler = GitView::builder().with_commit("HEAD~300").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::default();
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_revision()
+                .returning(|_| Ok(GitOutput::Err("".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_err());
+            let error = actual_final_url.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::InvalidRevision);
+            assert_eq!(
+                error.to_string(),
+                "Could not resolve 'HEAD~300' to a commit, expected a hash, branch, tag or expression such as 'HEAD~3'"
+            );
+        }
+
+        #[test]
+        fn is_latest_commit_with_path_current_working_directory() {
             let handler = GitView::builder()
-                .with_commit("8s2jl250as7f234jasfjj")
+                .with_commit("current")
+                .with_path("src/main.rs")
                 .build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "eafdb9a\nrefs/heads/main", "");
             let expected_final_url =
-                "https://github.com/sgoudham/git-view/tree/8s2jl250as7f234jasfjj";
+                "https://github.com/sgoudham/git-view/tree/eafdb9a/src/main.rs";
+
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_remote_branches_containing()
+                .returning(|_, _| Ok(GitOutput::Ok("origin/main".into())));
+            mock.expect_get_renames()
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+            mock.expect_get_tree_entry()
+                .returning(|_, _| Ok(GitOutput::Ok("entry".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test]
+        fn is_user_commit_with_renamed_path() {
+            let handler = GitView::builder()
+                .with_commit("current")
+                .with_path("src/new.rs")
+                .build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "eafdb9a\nrefs/heads/main", "");
+            let expected_final_url = "https://github.com/sgoudham/git-view/tree/eafdb9a/src/old.rs";
+
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_remote_branches_containing()
+                .returning(|_, _| Ok(GitOutput::Ok("origin/main".into())));
+            mock.expect_get_renames()
+                .with(eq("eafdb9a"), eq("src/new.rs"))
+                .returning(|_, _| {
+                    Ok(GitOutput::Ok(
+                        "R100\tsrc/mid.rs\tsrc/new.rs\nM\tsrc/mid.rs\nR090\tsrc/old.rs\tsrc/mid.rs"
+                            .into(),
+                    ))
+                });
+            mock.expect_get_tree_entry()
+                .with(eq("eafdb9a"), eq("src/old.rs"))
+                .returning(|_, _| Ok(GitOutput::Ok("entry".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test]
+        fn is_user_commit_with_missing_path() {
+            let handler = GitView::builder()
+                .with_commit("current")
+                .with_path("src/added.rs")
+                .build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "eafdb9a\nrefs/heads/main", "");
+
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_remote_branches_containing()
+                .returning(|_, _| Ok(GitOutput::Ok("origin/main".into())));
+            mock.expect_get_renames()
+                .returning(|_, _| Ok(GitOutput::Ok("A\tsrc/added.rs".into())));
+            mock.expect_get_tree_entry()
+                .returning(|_, _| Ok(GitOutput::Err("".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_err());
+            let error = actual_final_url.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::MissingPath);
+            assert_eq!(
+                error.to_string(),
+                "Path 'src/added.rs' did not exist at commit 'eafdb9a'"
+            );
+        }
+
+        #[test_case("main" ; "main")]
+        #[test_case("master" ; "master")]
+        fn is_master_or_main(branch: &str) {
+            let handler = GitView::default();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::default();
+            let expected_final_url = format!("https://github.com/sgoudham/git-view/tree/{branch}");
+            let mock = MockGitTrait::default();
+
+            let actual_final_url =
+                handler.generate_final_url(branch, "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test_case("main" ; "main")]
+        #[test_case("master" ; "master")]
+        fn is_master_or_main_with_issue_flag(branch: &str) {
+            let handler = GitView::builder().with_issue("branch").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/origin/main\0hash\0\0");
+            let expected_final_url = "https://github.com/sgoudham/git-view/issues";
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_commit_messages()
+                .with(eq("origin/main"))
+                .returning(|_| Ok(GitOutput::Ok("".into())));
+
+            let actual_final_url =
+                handler.generate_final_url(branch, "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test]
+        fn is_user_issue() {
+            let handler = GitView::builder().with_issue("branch").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::default();
+            let expected_final_url = "https://github.com/sgoudham/git-view/issues/1234";
+            let mock = MockGitTrait::default();
+
+            let actual_final_url =
+                handler.generate_final_url("TICKET-1234", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test]
+        fn is_user_issue_with_user_pattern() {
+            let handler = GitView::builder().with_issue("branch").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("view.issuepattern\nfix-(\\d+)\0", "", "");
+            let expected_final_url = "https://github.com/sgoudham/git-view/issues/42";
+            let mock = MockGitTrait::default();
+
+            let actual_final_url =
+                handler.generate_final_url("v2-fix-42", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test]
+        fn is_user_issue_with_unmatched_user_pattern() {
+            let handler = GitView::builder().with_issue("branch").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("view.issuepattern\nfix-(\\d+)\0", "", "");
             let mock = MockGitTrait::default();
 
-            let actual_final_url = handler.generate_final_url("main", &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url("TICKET-1234", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_err());
+            let error = actual_final_url.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::MissingIssueNumber);
+            assert_eq!(
+                error.to_string(),
+                r"Branch 'TICKET-1234' does not match the issue pattern 'fix-(\d+)' set in 'view.issuePattern'"
+            );
+        }
+
+        #[test_case("Fixes #12",                      "https://github.com/sgoudham/git-view/issues/12"    ; "with hashtag")]
+        #[test_case("Add feature\n\nCloses: GH-45",    "https://github.com/sgoudham/git-view/issues/45"    ; "with gh trailer")]
+        #[test_case("Refs sgoudham/other#9\n\nRefs sgoudham/other#9", "https://github.com/sgoudham/other/issues/9" ; "with cross repository")]
+        fn is_commit_issue(messages: &'static str, expected_final_url: &str) {
+            let handler = GitView::builder().with_issue("branch").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/origin/main\0hash\0\0");
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_commit_messages()
+                .returning(move |_| Ok(GitOutput::Ok(messages.into())));
+
+            let actual_final_url =
+                handler.generate_final_url("feature", "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
         }
 
         #[test]
-        fn is_latest_commit_with_path_current_working_directory() {
-            let handler = GitView::builder()
-                .with_commit("current")
-                .with_path("src/main.rs")
-                .build();
+        fn is_commit_issue_since_default_branch() {
+            let handler = GitView::builder().with_issue("branch").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse(
+                "",
+                "",
+                "refs/remotes/origin/HEAD\0hash\0\0refs/remotes/origin/develop\nrefs/remotes/origin/develop\0hash\0\0",
+            );
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_commit_messages()
+                .with(eq("origin/develop"))
+                .returning(|_| Ok(GitOutput::Ok("Fixes #12".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("feature", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(
+                actual_final_url.unwrap(),
+                "https://github.com/sgoudham/git-view/issues/12"
+            );
+        }
+
+        #[test]
+        fn is_commit_issue_without_default_branch() {
+            let handler = GitView::builder().with_issue("branch").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/upstream/trunk\0hash\0\0");
+            // The commits aren't scanned, so no expectation is set for them
+            let mock = MockGitTrait::default();
+
+            let actual_final_url =
+                handler.generate_final_url("feature", "upstream", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(
+                actual_final_url.unwrap(),
+                "https://github.com/sgoudham/git-view/issues"
+            );
+        }
+
+        #[test]
+        fn is_commit_issue_with_failed_log() {
+            let handler = GitView::builder().with_issue("branch").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/origin/main\0hash\0\0");
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_commit_messages()
+                .returning(|_| Ok(GitOutput::Err("fatal: bad revision".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("feature", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_err());
+            assert_eq!(
+                actual_final_url.unwrap_err().error_type(),
+                ErrorType::CommandFailed
+            );
+        }
+
+        #[test]
+        fn is_multiple_commit_issues() {
+            let handler = GitView::builder().with_issue("branch").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/origin/main\0hash\0\0");
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_commit_messages()
+                .returning(|_| Ok(GitOutput::Ok("Fixes #12\n\nRefs sgoudham/other#9".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("feature", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_err());
+            let error = actual_final_url.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::AmbiguousIssueReference);
+            assert_eq!(
+                error.to_string(),
+                "Found multiple issues referenced in commits since 'origin/main': #12, sgoudham/other#9\nPlease pick one with '--issue <number>'"
+            );
+        }
+
+        #[test]
+        fn is_user_issue_with_args() {
+            let handler = GitView::builder().with_issue("42").build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
-            let expected_final_url =
-                "https://github.com/sgoudham/git-view/tree/eafdb9a/src/main.rs";
-
-            let mut mock = MockGitTrait::default();
-            mock.expect_get_current_commit()
-                .returning(|| Ok(GitOutput::Ok("eafdb9a".into())));
+            let snapshot = RepoSnapshot::default();
+            let expected_final_url = "https://github.com/sgoudham/git-view/issues/42";
+            let mock = MockGitTrait::default();
 
-            let actual_final_url = handler.generate_final_url("main", &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
         }
 
-        #[test_case("main" ; "main")]
-        #[test_case("master" ; "master")]
-        fn is_master_or_main(branch: &str) {
-            let handler = GitView::default();
+        #[test_case("branch",       "PROJ-1234-thing",  "https://jira.corp/browse/PROJ-1234" ; "from branch")]
+        #[test_case("ENG-42",       "main",             "https://jira.corp/browse/ENG-42"    ; "from args")]
+        #[test_case("42",           "PROJ-1234-thing",  "https://github.com/sgoudham/git-view/issues/42" ; "from numeric args")]
+        fn is_issue_tracker(issue: &str, branch: &str, expected_final_url: &str) {
+            let handler = GitView::builder().with_issue(issue).build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
-            let expected_final_url = format!("https://github.com/sgoudham/git-view/tree/{branch}");
+            let snapshot = RepoSnapshot::parse(
+                "view.issuetracker\nhttps://jira.corp/browse/{key}\0",
+                "",
+                "",
+            );
             let mock = MockGitTrait::default();
 
-            let actual_final_url = handler.generate_final_url(branch, &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url(branch, "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
         }
 
-        #[test_case("main" ; "main")]
-        #[test_case("master" ; "master")]
-        fn is_master_or_main_with_issue_flag(branch: &str) {
+        #[test]
+        fn is_issue_tracker_with_user_key_pattern() {
             let handler = GitView::builder().with_issue("branch").build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
-            let expected_final_url = "https://github.com/sgoudham/git-view/issues";
+            let snapshot = RepoSnapshot::parse("view.issuetracker\nhttps://linear.app/team/issue/{key}\0view.issuekeypattern\n(eng-\\d+)\0", "", "");
+            let expected_final_url = "https://linear.app/team/issue/eng-42";
             let mock = MockGitTrait::default();
 
-            let actual_final_url = handler.generate_final_url(branch, &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url("user/eng-42-thing", "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
         }
 
         #[test]
-        fn is_user_issue() {
+        fn is_issue_tracker_without_key_in_branch() {
             let handler = GitView::builder().with_issue("branch").build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse(
+                "view.issuetracker\nhttps://jira.corp/browse/{key}\0",
+                "",
+                "",
+            );
             let expected_final_url = "https://github.com/sgoudham/git-view/issues/1234";
             let mock = MockGitTrait::default();
 
-            let actual_final_url = handler.generate_final_url("TICKET-1234", &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url("1234-thing", "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
         }
 
         #[test]
-        fn is_user_issue_with_args() {
-            let handler = GitView::builder().with_issue("42").build();
+        fn is_invalid_issue_tracker() {
+            let handler = GitView::builder().with_issue("branch").build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
-            let expected_final_url = "https://github.com/sgoudham/git-view/issues/42";
+            let snapshot =
+                RepoSnapshot::parse("view.issuetracker\nhttps://jira.corp/browse\0", "", "");
             let mock = MockGitTrait::default();
 
-            let actual_final_url = handler.generate_final_url("main", &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url("PROJ-1234", "origin", &url, &snapshot, &mock);
 
-            assert!(actual_final_url.is_ok());
-            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+            assert!(actual_final_url.is_err());
+            assert_eq!(
+                actual_final_url.unwrap_err().error_type(),
+                ErrorType::InvalidIssueTracker
+            );
         }
 
         #[test]
         fn is_normal_branch() {
             let handler = GitView::builder().build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::default();
             let expected_final_url = "https://github.com/sgoudham/git-view/tree/%23test%23";
             let mock = MockGitTrait::default();
 
-            let actual_final_url = handler.generate_final_url("#test#", &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url("#test#", "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
@@ -942,10 +3721,111 @@ mod lib_tests {
         fn is_user_path() {
             let handler = GitView::builder().with_path("src/main.rs").build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::default();
             let expected_final_url = "https://github.com/sgoudham/git-view/tree/main/src/main.rs";
-            let mock = MockGitTrait::default();
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_tree_listing()
+                .returning(|_, _| Ok(GitOutput::Ok("100644 blob hash\tpath".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test]
+        fn is_user_path_with_special_characters() {
+            let handler = GitView::builder().with_path("docs/What's new?.md").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::default();
+            let expected_final_url =
+                "https://github.com/sgoudham/git-view/tree/feature/a%2Bb/docs/What's%20new%3F.md";
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_tree_listing()
+                .returning(|_, _| Ok(GitOutput::Ok("100644 blob hash\tpath".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("feature/a+b", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test_case(MissingPath::Refuse, Err(ErrorType::MissingRemotePath)                                     ; "with refusal")]
+        #[test_case(MissingPath::Parent, Ok("https://github.com/sgoudham/git-view/tree/main/src/".into()) ; "with parent")]
+        fn is_user_path_missing_on_remote(
+            missing_path: MissingPath,
+            expected_final_url: Result<String, ErrorType>,
+        ) {
+            let handler = GitView::builder()
+                .with_path("src/bin/new.rs")
+                .with_missing_path(missing_path)
+                .build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/origin/main\0hash\0\0");
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_tree_listing()
+                .with(eq("refs/remotes/origin/main"), eq("src/bin/new.rs"))
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+            mock.expect_get_tree_listing()
+                .with(eq("refs/remotes/origin/main"), eq("src/bin"))
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+            mock.expect_get_tree_listing()
+                .with(eq("refs/remotes/origin/main"), eq("src"))
+                .returning(|_, _| Ok(GitOutput::Ok("040000 tree hash\tsrc".into())));
+            mock.expect_get_tracked_paths()
+                .with(eq("refs/remotes/origin/main"))
+                .returning(|_| Ok(GitOutput::Ok("src/lib.rs".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert_eq!(
+                actual_final_url.map_err(|error| error.error_type()),
+                expected_final_url
+            );
+        }
+
+        #[test]
+        fn is_user_path_missing_on_remote_and_suggested() {
+            let handler = GitView::builder().with_path("src/lib.sr").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/origin/main\0hash\0\0");
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_tree_listing()
+                .with(eq("refs/remotes/origin/main"), eq("src/lib.sr"))
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+            mock.expect_get_tracked_paths()
+                .with(eq("refs/remotes/origin/main"))
+                .returning(|_| Ok(GitOutput::Ok("README.md\nsrc/git.rs\nsrc/lib.rs".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_err());
+            assert_eq!(
+                actual_final_url.unwrap_err().to_string(),
+                "Path 'src/lib.sr' does not exist on 'origin/main', it may not have been committed or pushed, did you mean 'src/lib.rs'?\nView it with '--path src/lib.rs'"
+            );
+        }
 
-            let actual_final_url = handler.generate_final_url("main", &url, &mock);
+        #[test]
+        fn is_user_path_with_unknown_remote_ref() {
+            let handler = GitView::builder().with_path("src/main.rs").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::default();
+            let expected_final_url = "https://github.com/sgoudham/git-view/tree/v1.0.0/src/main.rs";
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_tree_listing()
+                .with(eq("v1.0.0"), eq("src/main.rs"))
+                .returning(|_, _| Ok(GitOutput::Err("fatal: Not a valid object name".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("v1.0.0", "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
@@ -957,74 +3837,613 @@ mod lib_tests {
                 .with_path("current-working-directory")
                 .build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "\nhash\nrefs/heads/main", "");
             let expected_final_url = "https://github.com/sgoudham/git-view";
 
-            let mut mock = MockGitTrait::default();
-            mock.expect_get_current_working_directory()
-                .returning(|| Ok(GitOutput::Ok("".into())));
+            let mock = MockGitTrait::default();
 
-            let actual_final_url = handler.generate_final_url("main", &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
         }
 
+        #[test_case(".",  "" ; "with current directory at root")]
+        #[test_case("..", "src/" ; "with parent directory to root")]
+        fn is_user_path_at_repo_root(path: &str, prefix: &str) {
+            let handler = GitView::builder().with_path(path).build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", &format!("{prefix}\nhash\nrefs/heads/dev"), "");
+            let mock = MockGitTrait::default();
+
+            let actual_final_url =
+                handler.generate_final_url("dev", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(
+                actual_final_url.unwrap(),
+                "https://github.com/sgoudham/git-view/tree/dev"
+            );
+        }
+
+        #[test]
+        fn is_user_path_missing_on_remote_defaulting_to_root() {
+            let handler = GitView::builder()
+                .with_path("new.rs")
+                .with_missing_path(MissingPath::Parent)
+                .build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "", "refs/remotes/origin/dev\0hash\0\0");
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_tree_listing()
+                .with(eq("refs/remotes/origin/dev"), eq("new.rs"))
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("dev", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(
+                actual_final_url.unwrap(),
+                "https://github.com/sgoudham/git-view/tree/dev"
+            );
+        }
+
         #[test]
         fn is_path_at_sub_directory() {
             let handler = GitView::builder()
                 .with_path("current-working-directory")
                 .build();
             let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "src/\nhash\nrefs/heads/main", "");
             let expected_final_url = "https://github.com/sgoudham/git-view/tree/main/src/";
 
             // `git rev-parse --show-prefix` returns relative path with a trailing slash
             let mut mock = MockGitTrait::default();
-            mock.expect_get_current_working_directory()
-                .returning(|| Ok(GitOutput::Ok("src/".into())));
+            mock.expect_get_tree_listing()
+                .returning(|_, _| Ok(GitOutput::Ok("100644 blob hash\tpath".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_ok());
+            assert_eq!(actual_final_url.unwrap(), expected_final_url);
+        }
+
+        #[test_case("lib.rs",       "https://github.com/sgoudham/git-view/tree/main/src/lib.rs" ; "with file")]
+        #[test_case("./bin/",       "https://github.com/sgoudham/git-view/tree/main/src/bin/"   ; "with current directory")]
+        #[test_case("../README.md", "https://github.com/sgoudham/git-view/tree/main/README.md"  ; "with parent directory")]
+        #[test_case("..",           "https://github.com/sgoudham/git-view/tree/main"            ; "with root")]
+        fn is_user_path_relative_to_sub_directory(path: &str, expected_final_url: &str) {
+            let handler = GitView::builder().with_path(path).build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "src/\nhash\nrefs/heads/main", "");
+            let mut mock = MockGitTrait::default();
+            mock.expect_get_tree_listing()
+                .returning(|_, _| Ok(GitOutput::Ok("100644 blob hash\tpath".into())));
 
-            let actual_final_url = handler.generate_final_url("main", &url, &mock);
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
 
             assert!(actual_final_url.is_ok());
             assert_eq!(actual_final_url.unwrap(), expected_final_url);
         }
+
+        #[test]
+        fn is_user_path_outside_repository() {
+            let handler = GitView::builder().with_path("../../etc/passwd").build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "src/\nhash\nrefs/heads/main", "");
+            let mock = MockGitTrait::default();
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert!(actual_final_url.is_err());
+            let error = actual_final_url.unwrap_err();
+            assert_eq!(error.error_type(), ErrorType::PathOutsideRepository);
+            assert_eq!(
+                error.to_string(),
+                "Path '../../etc/passwd' is outside of the repository"
+            );
+        }
+
+        #[test_case("src/lib.rs",                   Ok("https://github.com/sgoudham/git-view/tree/eafdb9a/src/lib.rs".into()) ; "relative to root")]
+        #[test_case("current-working-directory",    Err(ErrorType::BareRepository)                                          ; "current working directory")]
+        #[test_case("/srv/git/git-view.git/lib.rs", Err(ErrorType::BareRepository)                                          ; "absolute")]
+        fn is_user_path_in_bare_repository(
+            path: &str,
+            expected_final_url: Result<String, ErrorType>,
+        ) {
+            let handler = GitView::builder()
+                .with_commit("current")
+                .with_path(path)
+                .build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "true\n\neafdb9a\nrefs/heads/main", "");
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_renames()
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+            mock.expect_get_tree_entry()
+                .returning(|_, _| Ok(GitOutput::Ok("entry".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert_eq!(
+                actual_final_url.map_err(|error| error.error_type()),
+                expected_final_url
+            );
+        }
+
+        #[cfg(unix)]
+        #[test_case("/home/me/git-view/src/lib.rs",    Ok("https://github.com/sgoudham/git-view/tree/eafdb9a/src/lib.rs".into()) ; "inside repository")]
+        #[test_case("/home/me/git-view/src/../docs/",  Ok("https://github.com/sgoudham/git-view/tree/eafdb9a/docs/".into())   ; "with parent directory")]
+        #[test_case("/home/me/other/src/lib.rs",       Err(ErrorType::PathOutsideRepository)                                ; "outside repository")]
+        fn is_user_path_absolute(path: &str, expected_final_url: Result<String, ErrorType>) {
+            let handler = GitView::builder()
+                .with_commit("current")
+                .with_path(path)
+                .build();
+            let url = Url::new("https", "github.com", "sgoudham/git-view");
+            let snapshot = RepoSnapshot::parse("", "src/\neafdb9a\nrefs/heads/main", "");
+            let mut mock = MockGitTrait::default();
+
+            mock.expect_get_remote_branches_containing()
+                .returning(|_, _| Ok(GitOutput::Ok("origin/main".into())));
+            mock.expect_get_top_level()
+                .returning(|| Ok(GitOutput::Ok("/home/me/git-view".into())));
+            mock.expect_get_renames()
+                .returning(|_, _| Ok(GitOutput::Ok("".into())));
+            mock.expect_get_tree_entry()
+                .returning(|_, _| Ok(GitOutput::Ok("entry".into())));
+
+            let actual_final_url =
+                handler.generate_final_url("main", "origin", &url, &snapshot, &mock);
+
+            assert_eq!(
+                actual_final_url.map_err(|error| error.error_type()),
+                expected_final_url
+            );
+        }
     }
 
-    mod capture_digits {
+    mod capture_issue_number {
         use test_case::test_case;
 
-        use crate::capture_digits;
+        use crate::{capture_default_issue_number, capture_issue_number, error::ErrorType};
 
         #[test_case("🥵🥵Hazel🥵-1234🥵🥵",     "1234"                      ; "with emojis")]
         #[test_case("TICKET-1234-To-V10",       "1234"                      ; "with multiple issue numbers")]
         #[test_case("TICKET-1234",              "1234"                      ; "with issue number at end")]
         #[test_case("1234-TICKET",              "1234"                      ; "with issue number at start")]
         #[test_case("1234",                     "1234"                      ; "with no letters")]
-        fn branch(input: &str, expected_remote_ref: &str) {
-            let actual_remote_ref = capture_digits(input);
-            assert_eq!(actual_remote_ref, Some(expected_remote_ref));
+        #[test_case("ISSUE-123",                "123"                       ; "with issue prefix")]
+        #[test_case("feature/gh-123",           "123"                       ; "with gh prefix")]
+        #[test_case("issue/123",                "123"                       ; "with issue directory")]
+        #[test_case("v2-fix-1234",              "1234"                      ; "with version number")]
+        #[test_case("feature_123",              "123"                       ; "with underscore")]
+        #[test_case("bug42",                    "42"                        ; "with letters before")]
+        #[test_case("TICKET1234",               "1234"                      ; "with no separator")]
+        fn branch(input: &str, expected_issue_num: &str) {
+            let actual_issue_num = capture_default_issue_number(input);
+            assert_eq!(actual_issue_num, Ok(Some(expected_issue_num)));
         }
 
         #[test]
         fn branch_no_numbers() {
             let input = "TICKET-WITH-NO-NUMBERS";
-            let actual_remote_ref = capture_digits(input);
-            assert_eq!(actual_remote_ref, None);
+            let actual_issue_num = capture_default_issue_number(input);
+            assert_eq!(actual_issue_num, Ok(None));
+        }
+
+        #[test_case("team/TICKET-1234-42",  r"TICKET-(\d+)",   Some("1234")   ; "with match")]
+        #[test_case("team/1234-42",         r"TICKET-(\d+)",   None           ; "without match")]
+        fn user_pattern(input: &str, pattern: &str, expected_issue_num: Option<&str>) {
+            let actual_issue_num = capture_issue_number(input, pattern);
+            assert_eq!(actual_issue_num, Ok(expected_issue_num));
+        }
+
+        #[test_case(r"TICKET-\d+"     ; "without capture group")]
+        #[test_case(r"TICKET-(\d+"    ; "with invalid regex")]
+        fn invalid_user_pattern(pattern: &str) {
+            let actual_issue_num = capture_issue_number("TICKET-1234", pattern);
+
+            assert!(actual_issue_num.is_err());
+            assert_eq!(
+                actual_issue_num.unwrap_err().error_type(),
+                ErrorType::InvalidIssuePattern
+            );
+        }
+    }
+
+    mod capture_issue_references {
+        use test_case::test_case;
+
+        use crate::{capture_issue_references, IssueReference};
+
+        #[test_case("Fixes #123",           None,                       "123"   ; "with fixes")]
+        #[test_case("closes: GH-45",        None,                       "45"    ; "with closes trailer")]
+        #[test_case("Resolved #7",          None,                       "7"     ; "with resolved")]
+        #[test_case("Refs #9",              None,                       "9"     ; "with refs")]
+        #[test_case("Refs owner/repo#12",   Some("owner/repo"),         "12"    ; "with cross repository")]
+        fn message(message: &str, repository: Option<&str>, number: &str) {
+            let actual_references = capture_issue_references(message);
+            assert_eq!(
+                actual_references,
+                vec![IssueReference { repository, number }]
+            );
+        }
+
+        #[test]
+        fn messages_with_duplicates() {
+            let messages = "Fixes #1\n\nSome other commit\nFixes #2\nRefs #1";
+
+            let actual_references = capture_issue_references(messages);
+
+            assert_eq!(
+                actual_references,
+                vec![
+                    IssueReference {
+                        repository: None,
+                        number: "1"
+                    },
+                    IssueReference {
+                        repository: None,
+                        number: "2"
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn messages_without_references() {
+            let messages = "Mentions issue #1 without a keyword";
+            let actual_references = capture_issue_references(messages);
+            assert_eq!(actual_references, vec![]);
+        }
+    }
+
+    mod normalize_path {
+        use test_case::test_case;
+
+        use crate::normalize_path;
+
+        #[test_case("",         "src/lib.rs",       Some("src/lib.rs")  ; "at root")]
+        #[test_case("src/",     "lib.rs",           Some("src/lib.rs")  ; "at sub directory")]
+        #[test_case("src/",     "",                 Some("src/")        ; "with current working directory")]
+        #[test_case("src/",     "./bin/./main.rs",  Some("src/bin/main.rs") ; "with current directory")]
+        #[test_case("src/bin/", "../../docs/",      Some("docs/")       ; "with parent directories")]
+        #[test_case("src/",     "..",               Some("")            ; "with root")]
+        #[test_case("src/",     "../..",            None                ; "with outside repository")]
+        #[test_case("",         "a//b",             Some("a/b")         ; "with empty segment")]
+        fn normalize(directory: &str, path: &str, expected_path: Option<&str>) {
+            assert_eq!(normalize_path(directory, path).as_deref(), expected_path);
         }
     }
 
-    mod escape_ascii_chars {
+    mod encode_path {
+        use percent_encoding::percent_decode_str;
+        use proptest::{prop_assert_eq, prop_assume, proptest};
         use test_case::test_case;
 
-        use crate::escape_ascii_chars;
+        use crate::encode_path;
 
-        #[test_case("🥵🥵Hazel🥵-%1234#🥵🥵",   "🥵🥵Hazel🥵-%251234%23🥵🥵"    ; "with emojis")]
+        #[test_case("🥵🥵Hazel🥵-%1234#🥵🥵",   "%F0%9F%A5%B5%F0%9F%A5%B5Hazel%F0%9F%A5%B5-%251234%23%F0%9F%A5%B5%F0%9F%A5%B5" ; "with emojis")]
         #[test_case("TICKET-%1234#",            "TICKET-%251234%23"             ; "with hashtag and percentage")]
         #[test_case("TICKET-%1234",             "TICKET-%251234"                ; "with percentage")]
         #[test_case("TICKET-#1234",             "TICKET-%231234"                ; "with hashtag")]
+        #[test_case("TICKET 1234?a=b&c+d",      "TICKET%201234%3Fa%3Db%26c%2Bd" ; "with query characters")]
+        #[test_case("feature/TICKET",           "feature/TICKET"                ; "with slash")]
         #[test_case("TICKET",                   "TICKET"                        ; "with only alphabet")]
         fn branch(input: &str, expected_remote_ref: &str) {
-            let actual_remote_ref = escape_ascii_chars(input);
+            let actual_remote_ref = encode_path(input);
             assert_eq!(actual_remote_ref, expected_remote_ref);
         }
+
+        proptest! {
+            #[test]
+            fn round_trips_through_url(path in "[^/]{1,10}(/[^/]{1,10}){0,3}") {
+                prop_assume!(path.split('/').all(|segment| segment != "." && segment != ".."));
+
+                let encoded_path = encode_path(&path);
+                let url = url::Url::parse(&format!("https://github.com/sgoudham/git-view/tree/{encoded_path}")).unwrap();
+
+                prop_assert_eq!(url.path(), format!("/sgoudham/git-view/tree/{encoded_path}"));
+                let decoded_path = url
+                    .path_segments()
+                    .unwrap()
+                    .skip(3)
+                    .map(|segment| percent_decode_str(segment).decode_utf8().unwrap().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                prop_assert_eq!(decoded_path, path);
+            }
+        }
+    }
+
+    mod git {
+        use std::{env, fs, path::PathBuf, process::Command};
+
+        use crate::{
+            git::{GitOutput, GitTrait},
+            Git,
+        };
+
+        /// A repository in the temp directory that the backends are run against, removed when
+        /// dropped
+        pub(super) struct TempRepository(pub(super) PathBuf);
+
+        impl TempRepository {
+            pub(super) fn new(name: &str) -> Self {
+                let directory =
+                    env::temp_dir().join(format!("git-view-{}-{}", std::process::id(), name));
+                let _ = fs::remove_dir_all(&directory);
+                fs::create_dir_all(directory.join("src")).unwrap();
+                let repository = Self(directory);
+
+                repository.git(&["init", "--quiet"]);
+                repository.git(&["symbolic-ref", "HEAD", "refs/heads/main"]);
+                fs::write(repository.0.join("README.md"), "# git-view").unwrap();
+                fs::write(repository.0.join("src/lib.rs"), "").unwrap();
+                repository.git(&["add", "."]);
+                repository.git(&["commit", "--quiet", "--message", "Initial commit"]);
+
+                repository
+            }
+
+            pub(super) fn git(&self, args: &[&str]) -> String {
+                let output = Command::new("git")
+                    .arg("-C")
+                    .arg(&self.0)
+                    .args([
+                        "-c",
+                        "user.name=git-view",
+                        "-c",
+                        "user.email=git-view@example.com",
+                    ])
+                    .args(args)
+                    .output()
+                    .unwrap();
+                assert!(output.status.success(), "git {:?} failed", args);
+                String::from_utf8(output.stdout).unwrap()
+            }
+        }
+
+        impl Drop for TempRepository {
+            fn drop(&mut self) {
+                let _ = fs::remove_dir_all(&self.0);
+            }
+        }
+
+        pub(super) fn output(output: GitOutput) -> String {
+            match output {
+                GitOutput::Ok(output) => output,
+                GitOutput::Err(err) => panic!("{}", err),
+            }
+        }
+
+        #[test]
+        fn is_run_from_directory() {
+            // The tests run from the crate's own repository, so any query that isn't run from
+            // the directory answers about the wrong repository
+            let repository = TempRepository::new("directory");
+            repository.git(&[
+                "remote",
+                "add",
+                "origin",
+                "https://github.com/sgoudham/git-view.git",
+            ]);
+            fs::write(
+                repository.0.join(".gitmodules"),
+                "[submodule \"lib\"]\n\tpath = lib\n\turl = https://github.com/sgoudham/lib.git\n",
+            )
+            .unwrap();
+            repository.git(&["add", ".gitmodules"]);
+            repository.git(&["commit", "--quiet", "--message", "Add submodule"]);
+            repository.git(&["tag", "v1.0.0"]);
+            let head = repository.git(&["rev-parse", "HEAD"]).trim().to_string();
+            let git = Git::default().with_directory(&repository.0);
+
+            assert!(output(git.get_config_list().unwrap()).contains("remote.origin.url"));
+            assert_eq!(
+                output(git.get_head().unwrap()),
+                format!("false\n\n{head}\nrefs/heads/main")
+            );
+            assert!(output(git.get_refs().unwrap()).contains("refs/tags/v1.0.0"));
+            assert_eq!(
+                fs::canonicalize(output(git.get_top_level().unwrap())).unwrap(),
+                fs::canonicalize(&repository.0).unwrap()
+            );
+            assert!(output(git.get_submodules().unwrap()).contains("submodule.lib.path"));
+            assert_eq!(output(git.get_submodule_branch(".").unwrap()), "main");
+            assert!(!output(git.get_tree_entry("HEAD", "README.md").unwrap()).is_empty());
+            assert!(output(git.get_tree_listing("HEAD", "src").unwrap()).contains("src"));
+            assert_eq!(
+                output(git.get_tracked_paths("HEAD").unwrap()),
+                ".gitmodules\nREADME.md\nsrc/lib.rs"
+            );
+            assert_eq!(output(git.get_revision("v1.0.0").unwrap()), head);
+            assert_eq!(
+                output(git.get_symbolic_branch("main").unwrap()),
+                "refs/heads/main"
+            );
+            assert_eq!(
+                output(git.get_commit_message(&head).unwrap()),
+                "Add submodule"
+            );
+            assert_eq!(output(git.get_commit_messages(&head).unwrap()), "");
+            assert_eq!(output(git.get_renames(&head, "README.md").unwrap()), "");
+            assert_eq!(
+                output(git.get_remote_branches_containing("origin", &head).unwrap()),
+                ""
+            );
+            assert!(output(git.get_pushed_boundary("origin", &head).unwrap()).starts_with(&head));
+            assert_eq!(output(git.get_merge_messages(&head, "HEAD").unwrap()), "");
+            assert!(matches!(
+                git.get_rebase_head_name().unwrap(),
+                GitOutput::Err(_)
+            ));
+            assert!(matches!(git.checkout("v1.0.0").unwrap(), GitOutput::Ok(_)));
+            assert_eq!(
+                output(git.get_head().unwrap()),
+                format!("false\n\n{head}\nHEAD")
+            );
+        }
+
+        #[test]
+        fn is_unborn_head_run_from_directory() {
+            let repository = TempRepository::new("unborn-directory");
+            repository.git(&["checkout", "--quiet", "--orphan", "unborn"]);
+            let git = Git::default().with_directory(repository.0.join("src"));
+
+            assert_eq!(
+                output(git.get_head().unwrap()),
+                "false\nsrc/\n\nrefs/heads/unborn"
+            );
+        }
+    }
+
+    #[cfg(feature = "libgit2")]
+    mod libgit {
+        use std::fs;
+
+        use test_case::test_case;
+
+        use super::git::{output, TempRepository};
+        use crate::{git::GitTrait, snapshot::RepoSnapshot, Git, LibGit};
+
+        impl TempRepository {
+            fn backends(&self) -> (Git, LibGit) {
+                (
+                    Git::default().with_directory(&self.0),
+                    LibGit::default().with_directory(&self.0),
+                )
+            }
+        }
+
+        #[test]
+        fn is_config_list_equal() {
+            let repository = TempRepository::new("config");
+            repository.git(&[
+                "remote",
+                "add",
+                "origin",
+                "git@github.com:sgoudham/git-view.git",
+            ]);
+            // Keys without a value, e.g. `[view] flag`, can only be written to the file directly
+            let config = repository.0.join(".git/config");
+            let contents = fs::read_to_string(&config).unwrap();
+            fs::write(&config, format!("{contents}[view]\n\tflag\n")).unwrap();
+            let (git, lib_git) = repository.backends();
+
+            let expected_config = output(git.get_config_list().unwrap());
+            let actual_config = output(lib_git.get_config_list().unwrap());
+
+            assert_eq!(actual_config, expected_config);
+            assert_eq!(
+                RepoSnapshot::new(&lib_git).unwrap().config("view.flag"),
+                Some("true")
+            );
+        }
+
+        #[test_case(""    ; "at root")]
+        #[test_case("src" ; "in sub directory")]
+        fn is_head_equal(directory: &str) {
+            let repository = TempRepository::new(&format!("head-{directory}"));
+            let directory = repository.0.join(directory);
+            let (git, lib_git) = (
+                Git::default().with_directory(&directory),
+                LibGit::default().with_directory(&directory),
+            );
+
+            let expected_head = output(git.get_head().unwrap());
+            let actual_head = output(lib_git.get_head().unwrap());
+
+            assert_eq!(actual_head, expected_head);
+        }
+
+        #[test]
+        fn is_detached_head_equal() {
+            let repository = TempRepository::new("detached-head");
+            repository.git(&["checkout", "--quiet", "--detach"]);
+            let (git, lib_git) = repository.backends();
+
+            let expected_head = output(git.get_head().unwrap());
+            let actual_head = output(lib_git.get_head().unwrap());
+
+            assert_eq!(actual_head, expected_head);
+        }
+
+        #[test]
+        fn is_unborn_head_equal() {
+            let repository = TempRepository::new("unborn-head");
+            repository.git(&["checkout", "--quiet", "--orphan", "unborn"]);
+            let (git, lib_git) = repository.backends();
+
+            let expected_head = output(git.get_head().unwrap());
+            let actual_head = output(lib_git.get_head().unwrap());
+
+            assert_eq!(actual_head, expected_head);
+            let snapshot = RepoSnapshot::new(&lib_git).unwrap();
+            assert_eq!(snapshot.current_commit(), None);
+            assert_eq!(snapshot.current_branch(), Some("unborn"));
+        }
+
+        #[test]
+        fn is_refs_equal() {
+            let repository = TempRepository::new("refs");
+            repository.git(&["branch", "feature"]);
+            repository.git(&["update-ref", "refs/remotes/origin/main", "HEAD"]);
+            repository.git(&[
+                "symbolic-ref",
+                "refs/remotes/origin/HEAD",
+                "refs/remotes/origin/main",
+            ]);
+            repository.git(&["tag", "v1.0.0"]);
+            repository.git(&["tag", "--annotate", "--message", "Release", "v2.0.0"]);
+            // A symbolic ref to a branch that was since deleted
+            repository.git(&[
+                "symbolic-ref",
+                "refs/remotes/upstream/HEAD",
+                "refs/remotes/upstream/gone",
+            ]);
+            let (git, lib_git) = repository.backends();
+
+            let expected_refs = output(git.get_refs().unwrap());
+            let actual_refs = output(lib_git.get_refs().unwrap());
+
+            let mut expected_refs = expected_refs.lines().collect::<Vec<_>>();
+            let mut actual_refs = actual_refs.lines().collect::<Vec<_>>();
+            expected_refs.sort_unstable();
+            actual_refs.sort_unstable();
+            assert_eq!(actual_refs, expected_refs);
+        }
+
+        #[test_case("src/lib.rs" ; "with file")]
+        #[test_case("src"        ; "with directory")]
+        #[test_case("src/new.rs" ; "with missing path")]
+        fn is_tree_listing_equal(path: &str) {
+            let repository = TempRepository::new(&format!("tree-{}", path.replace('/', "-")));
+            let (git, lib_git) = repository.backends();
+
+            let expected_listing = output(git.get_tree_listing("main", path).unwrap());
+            let actual_listing = output(lib_git.get_tree_listing("main", path).unwrap());
+
+            assert_eq!(actual_listing, expected_listing);
+        }
+
+        #[test]
+        fn is_tracked_paths_equal() {
+            let repository = TempRepository::new("tracked-paths");
+            let (git, lib_git) = repository.backends();
+
+            let expected_paths = output(git.get_tracked_paths("main").unwrap());
+            let actual_paths = output(lib_git.get_tracked_paths("main").unwrap());
+
+            assert_eq!(actual_paths, expected_paths);
+        }
     }
 }
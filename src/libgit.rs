@@ -0,0 +1,476 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use git2::{
+    build::CheckoutBuilder, BranchType, Config, Delta, DiffFindOptions, ErrorCode, ObjectType, Oid,
+    Repository, Sort, TreeWalkMode, TreeWalkResult,
+};
+
+use crate::{
+    error::AppError,
+    git::{GitOutput, GitTrait},
+};
+
+/// In-process alternative to [`crate::Git`] that reads config, refs and remotes through
+/// libgit2 instead of spawning a `git` process for every query
+pub struct LibGit {
+    repository: Result<Repository, git2::Error>,
+    /// Directory that the repository was discovered from, used as the current working directory
+    directory: Option<PathBuf>,
+}
+
+impl Default for LibGit {
+    fn default() -> Self {
+        Self {
+            repository: Repository::open_from_env(),
+            directory: None,
+        }
+    }
+}
+
+impl GitTrait for LibGit {
+    fn get_config_list(&self) -> Result<GitOutput, AppError> {
+        // Mirrors `git config --list -z`, ordered from the system to the local config, which lists
+        // the global config outside of a repository too
+        let config = match self.repository.as_ref() {
+            Ok(repository) => repository.config(),
+            Err(_) => Config::open_default(),
+        };
+        match config.and_then(|config| config_list(&config)) {
+            Ok(list) => Ok(GitOutput::Ok(list)),
+            Err(err) => Ok(GitOutput::Err(err.message().into())),
+        }
+    }
+
+    fn get_head(&self) -> Result<GitOutput, AppError> {
+        // Mirrors `git rev-parse --is-bare-repository --show-prefix HEAD --symbolic-full-name HEAD`,
+        // falling back to `git symbolic-ref --quiet HEAD` without a commit when HEAD is unborn
+        self.run(|repository| {
+            // HEAD has no commit when it is unborn, and no name when it is detached
+            let commit = match repository.head().and_then(|head| head.peel_to_commit()) {
+                Ok(commit) => commit.id().to_string(),
+                Err(_) => String::new(),
+            };
+            let full_name = match repository.find_reference("HEAD")?.symbolic_target() {
+                Some(full_name) => full_name.to_string(),
+                None => "HEAD".into(),
+            };
+
+            Ok(format!(
+                "{}\n{}\n{}\n{}",
+                repository.is_bare(),
+                prefix(repository, self.directory.as_ref())?,
+                commit,
+                full_name
+            ))
+        })
+    }
+
+    fn get_refs(&self) -> Result<GitOutput, AppError> {
+        // Mirrors `git for-each-ref --format=%(refname)%00%(objectname)%00%(*objectname)%00%(symref)`
+        self.run(|repository| {
+            let mut refs = Vec::new();
+            for glob in ["refs/heads/*", "refs/remotes/*", "refs/tags/*"] {
+                for reference in repository.references_glob(glob)? {
+                    let reference = reference?;
+                    // Dangling symbolic refs are skipped, as git does
+                    let object = match reference.resolve().map(|resolved| resolved.target()) {
+                        Ok(Some(object)) => object,
+                        _ => continue,
+                    };
+                    let peeled = match repository.find_tag(object) {
+                        Ok(tag) => tag.target_id().to_string(),
+                        Err(_) => String::new(),
+                    };
+
+                    refs.push(format!(
+                        "{}\0{}\0{}\0{}",
+                        reference.name().unwrap_or_default(),
+                        object,
+                        peeled,
+                        reference.symbolic_target().unwrap_or_default()
+                    ));
+                }
+            }
+
+            Ok(refs.join("\n"))
+        })
+    }
+
+    fn get_top_level(&self) -> Result<GitOutput, AppError> {
+        self.run(|repository| match repository.workdir() {
+            Some(workdir) => Ok(workdir
+                .to_string_lossy()
+                .trim_end_matches(std::path::MAIN_SEPARATOR)
+                .into()),
+            None => Err(git2::Error::from_str(
+                "this operation must be run in a work tree",
+            )),
+        })
+    }
+
+    fn get_submodules(&self) -> Result<GitOutput, AppError> {
+        // Mirrors `git config --blob HEAD:.gitmodules --list -z`
+        self.run(|repository| {
+            let mut list = String::new();
+            for submodule in repository.submodules()? {
+                let name = submodule.name().unwrap_or_default();
+                list.push_str(&format!(
+                    "submodule.{}.path\n{}\0",
+                    name,
+                    submodule.path().to_string_lossy()
+                ));
+                if let Some(url) = submodule.url() {
+                    list.push_str(&format!("submodule.{}.url\n{}\0", name, url));
+                }
+            }
+
+            Ok(list)
+        })
+    }
+
+    fn get_submodule_branch(&self, directory: &str) -> Result<GitOutput, AppError> {
+        self.run(|_| {
+            let submodule = Repository::open(directory)?;
+            let head = submodule.head()?;
+            match head.is_branch() {
+                true => Ok(head.shorthand().unwrap_or_default().into()),
+                false => Err(git2::Error::from_str("HEAD is detached")),
+            }
+        })
+    }
+
+    fn get_tree_entry(&self, commit: &str, path: &str) -> Result<GitOutput, AppError> {
+        self.run(|repository| {
+            let tree = repository.revparse_single(commit)?.peel_to_tree()?;
+            Ok(tree.get_path(Path::new(path))?.id().to_string())
+        })
+    }
+
+    fn get_tree_listing(&self, reference: &str, path: &str) -> Result<GitOutput, AppError> {
+        // Mirrors `git ls-tree --full-tree <reference> -- <path>`, which lists nothing for
+        // missing paths
+        self.run(|repository| {
+            let tree = repository.revparse_single(reference)?.peel_to_tree()?;
+            let entry = match tree.get_path(Path::new(path)) {
+                Ok(entry) => entry,
+                Err(err) if err.code() == ErrorCode::NotFound => return Ok(String::new()),
+                Err(err) => return Err(err),
+            };
+
+            Ok(format!(
+                "{:06o} {} {}\t{}",
+                entry.filemode(),
+                entry.kind().map(|kind| kind.str()).unwrap_or_default(),
+                entry.id(),
+                path
+            ))
+        })
+    }
+
+    fn get_tracked_paths(&self, reference: &str) -> Result<GitOutput, AppError> {
+        // Mirrors `git ls-tree -r --full-tree --name-only <reference>`
+        self.run(|repository| {
+            let tree = repository.revparse_single(reference)?.peel_to_tree()?;
+            let mut paths = Vec::new();
+            tree.walk(TreeWalkMode::PreOrder, |directory, entry| {
+                if entry.kind() != Some(ObjectType::Tree) {
+                    paths.push(format!("{directory}{}", entry.name().unwrap_or_default()));
+                }
+                TreeWalkResult::Ok
+            })?;
+
+            Ok(paths.join("\n"))
+        })
+    }
+
+    fn get_renames(&self, commit: &str, path: &str) -> Result<GitOutput, AppError> {
+        // Mirrors `git log --follow --name-status --format= <commit>..HEAD -- <path>`, which
+        // walks every commit but, like `git log`, shows no changes for merge commits
+        self.run(|repository| {
+            let mut revwalk = repository.revwalk()?;
+            revwalk.set_sorting(Sort::TOPOLOGICAL)?;
+            revwalk.push_head()?;
+            revwalk.hide(peel_to_commit_id(repository, commit)?)?;
+
+            let mut name = path.to_string();
+            let mut changes = Vec::new();
+            for id in revwalk {
+                let commit = repository.find_commit(id?)?;
+                if commit.parent_count() > 1 {
+                    continue;
+                }
+                let parent_tree = match commit.parents().next() {
+                    Some(parent) => Some(parent.tree()?),
+                    None => None,
+                };
+                let mut diff = repository.diff_tree_to_tree(
+                    parent_tree.as_ref(),
+                    Some(&commit.tree()?),
+                    None,
+                )?;
+                diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+                for delta in diff.deltas() {
+                    let new_path = delta.new_file().path().and_then(Path::to_str);
+                    if new_path != Some(name.as_str()) {
+                        continue;
+                    }
+                    match delta.status() {
+                        Delta::Renamed => {
+                            let old_path = delta
+                                .old_file()
+                                .path()
+                                .and_then(Path::to_str)
+                                .unwrap_or_default();
+                            changes.push(format!("R100\t{}\t{}", old_path, name));
+                            name = old_path.into();
+                        }
+                        Delta::Added => changes.push(format!("A\t{}", name)),
+                        _ => changes.push(format!("M\t{}", name)),
+                    }
+                    break;
+                }
+            }
+
+            Ok(changes.join("\n"))
+        })
+    }
+
+    fn checkout(&self, reference: &str) -> Result<GitOutput, AppError> {
+        // Mirrors `git checkout <reference>`, which creates a local branch from the only
+        // remote-tracking branch of the same name when there is no local branch
+        self.run(|repository| {
+            if repository
+                .find_branch(reference, BranchType::Local)
+                .is_err()
+            {
+                let mut remote_branches = Vec::new();
+                for branch in repository.branches(Some(BranchType::Remote))? {
+                    let (branch, _) = branch?;
+                    let name = branch.name()?.unwrap_or_default();
+                    if name.split_once('/').map(|(_, name)| name) == Some(reference) {
+                        remote_branches.push((name.to_string(), branch.get().peel_to_commit()?));
+                    }
+                }
+                if let [(upstream, commit)] = remote_branches.as_slice() {
+                    repository
+                        .branch(reference, commit, false)?
+                        .set_upstream(Some(upstream))?;
+                }
+            }
+
+            let object = repository.revparse_single(reference)?;
+            repository.checkout_tree(&object, Some(CheckoutBuilder::new().safe()))?;
+            match repository.find_branch(reference, BranchType::Local) {
+                Ok(branch) => repository.set_head(branch.get().name().unwrap_or_default())?,
+                Err(_) => repository.set_head_detached(object.peel_to_commit()?.id())?,
+            }
+
+            Ok(String::new())
+        })
+    }
+
+    fn get_rebase_head_name(&self) -> Result<GitOutput, AppError> {
+        // Mirrors reading `rebase-merge/head-name` or `rebase-apply/head-name` from the git directory
+        self.run(|repository| {
+            ["rebase-merge", "rebase-apply"]
+                .iter()
+                .find_map(|state| {
+                    fs::read_to_string(repository.path().join(state).join("head-name")).ok()
+                })
+                .map(|head_name| head_name.trim().into())
+                .ok_or_else(|| git2::Error::from_str("No rebase in progress"))
+        })
+    }
+
+    fn get_revision(&self, revision: &str) -> Result<GitOutput, AppError> {
+        self.run(|repository| Ok(peel_to_commit_id(repository, revision)?.to_string()))
+    }
+
+    fn get_symbolic_branch(&self, branch: &str) -> Result<GitOutput, AppError> {
+        self.run(|repository| match repository.revparse_ext(branch)? {
+            (_, Some(reference)) => Ok(reference.name().unwrap_or_default().into()),
+            (_, None) => Ok(String::new()),
+        })
+    }
+
+    fn get_remote_branches_containing(
+        &self,
+        remote: &str,
+        commit: &str,
+    ) -> Result<GitOutput, AppError> {
+        self.run(|repository| {
+            let commit = peel_to_commit_id(repository, commit)?;
+
+            let mut branches = Vec::new();
+            for reference in repository.references_glob(&format!("refs/remotes/{}/*", remote))? {
+                let reference = reference?;
+                if let Some(target) = reference.target() {
+                    if target == commit || repository.graph_descendant_of(target, commit)? {
+                        branches.push(reference.shorthand().unwrap_or_default().to_string());
+                    }
+                }
+            }
+
+            Ok(branches.join("\n"))
+        })
+    }
+
+    fn get_pushed_boundary(&self, remote: &str, commit: &str) -> Result<GitOutput, AppError> {
+        // Mirrors `git rev-list --boundary <commit> --not --remotes=<remote>`
+        self.run(|repository| {
+            let mut revwalk = repository.revwalk()?;
+            revwalk.set_sorting(Sort::TOPOLOGICAL)?;
+            revwalk.push(peel_to_commit_id(repository, commit)?)?;
+            revwalk.hide_glob(&format!("refs/remotes/{}/*", remote))?;
+            let unpushed = revwalk.collect::<Result<Vec<Oid>, _>>()?;
+
+            let mut boundary = Vec::new();
+            for id in &unpushed {
+                for parent in repository.find_commit(*id)?.parent_ids() {
+                    if !unpushed.contains(&parent) && !boundary.contains(&parent) {
+                        boundary.push(parent);
+                    }
+                }
+            }
+
+            Ok(unpushed
+                .iter()
+                .map(|id| id.to_string())
+                .chain(boundary.iter().map(|id| format!("-{}", id)))
+                .collect::<Vec<_>>()
+                .join("\n"))
+        })
+    }
+
+    fn get_commit_messages(&self, base: &str) -> Result<GitOutput, AppError> {
+        self.run(|repository| {
+            let mut revwalk = repository.revwalk()?;
+            revwalk.push_head()?;
+            revwalk.hide(peel_to_commit_id(repository, base)?)?;
+
+            let mut messages = Vec::new();
+            for id in revwalk {
+                messages.push(
+                    repository
+                        .find_commit(id?)?
+                        .message()
+                        .unwrap_or_default()
+                        .to_string(),
+                );
+            }
+
+            Ok(messages.join("\n"))
+        })
+    }
+
+    fn get_commit_message(&self, commit: &str) -> Result<GitOutput, AppError> {
+        self.run(|repository| {
+            let commit = repository.find_commit(peel_to_commit_id(repository, commit)?)?;
+            Ok(commit.message().unwrap_or_default().trim_end().into())
+        })
+    }
+
+    fn get_merge_messages(&self, commit: &str, base: &str) -> Result<GitOutput, AppError> {
+        // Mirrors `git log --ancestry-path --merges --reverse --format=%B%x00 <commit>..<base>`
+        self.run(|repository| {
+            let commit = peel_to_commit_id(repository, commit)?;
+            let mut revwalk = repository.revwalk()?;
+            revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+            revwalk.push(peel_to_commit_id(repository, base)?)?;
+            revwalk.hide(commit)?;
+
+            let mut messages = Vec::new();
+            for id in revwalk {
+                let merge = repository.find_commit(id?)?;
+                if merge.parent_count() > 1 && repository.graph_descendant_of(merge.id(), commit)? {
+                    messages.push(format!("{}\0", merge.message().unwrap_or_default()));
+                }
+            }
+
+            Ok(messages.join("\n"))
+        })
+    }
+}
+
+impl LibGit {
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        let directory = directory.into();
+        self.repository = Repository::discover(&directory);
+        self.directory = Some(directory);
+        self
+    }
+
+    /// Failures are reported as [`GitOutput::Err`], the same as a `git` process exiting unsuccessfully
+    fn run(
+        &self,
+        query: impl FnOnce(&Repository) -> Result<String, git2::Error>,
+    ) -> Result<GitOutput, AppError> {
+        match self.repository.as_ref() {
+            Ok(repository) => match query(repository) {
+                Ok(output) => Ok(GitOutput::Ok(output)),
+                Err(err) => Ok(GitOutput::Err(err.message().into())),
+            },
+            Err(err) => Ok(GitOutput::Err(err.message().into())),
+        }
+    }
+}
+
+fn config_list(config: &Config) -> Result<String, git2::Error> {
+    let mut entries = config.entries(None)?;
+
+    let mut list = Vec::new();
+    while let Some(entry) = entries.next() {
+        let entry = entry?;
+        let name = entry.name().unwrap_or_default();
+        // Keys without a value are listed without the newline, as git does
+        let line = if entry.has_value() {
+            format!("{}\n{}\0", name, entry.value().unwrap_or_default())
+        } else {
+            format!("{name}\0")
+        };
+        list.push((entry.level() as i32, line));
+    }
+    list.sort_by_key(|(level, _)| *level);
+
+    Ok(list.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// Mirrors `git rev-parse --show-prefix` which has a trailing slash in sub directories
+fn prefix(repository: &Repository, directory: Option<&PathBuf>) -> Result<String, git2::Error> {
+    // Bare repositories have no working directory to be inside of
+    let workdir = match repository.workdir() {
+        Some(workdir) => workdir,
+        None => return Ok(String::new()),
+    };
+    let (workdir, cwd) = match (
+        workdir.canonicalize(),
+        match directory {
+            Some(directory) => directory.canonicalize(),
+            None => env::current_dir().and_then(|cwd| cwd.canonicalize()),
+        },
+    ) {
+        (Ok(workdir), Ok(cwd)) => (workdir, cwd),
+        (Err(err), _) | (_, Err(err)) => return Err(git2::Error::from_str(&err.to_string())),
+    };
+
+    match cwd.strip_prefix(&workdir) {
+        Ok(prefix) if prefix.as_os_str().is_empty() => Ok(String::new()),
+        Ok(prefix) => Ok(format!(
+            "{}/",
+            prefix
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        )),
+        Err(_) => Err(git2::Error::from_str("outside of the working directory")),
+    }
+}
+
+fn peel_to_commit_id(repository: &Repository, revision: &str) -> Result<Oid, git2::Error> {
+    Ok(repository.revparse_single(revision)?.peel_to_commit()?.id())
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::{AppError, ErrorType},
+    git::{GitOutput, GitTrait},
+};
+
+/// The config, HEAD and refs of the repository, gathered once up front so that resolving the
+/// remote and branch doesn't spawn a `git` process for every lookup
+#[derive(Debug, Default)]
+pub(crate) struct RepoSnapshot {
+    /// Entries in the order given by `git config --list`, later entries take precedence
+    config: Vec<(String, String)>,
+    /// Path of the current working directory relative to the root of the repository
+    prefix: String,
+    /// Bare repositories have no working directory, linked worktrees of them do
+    is_bare: bool,
+    commit: Option<String>,
+    branch: Option<String>,
+    /// Local branches, remote-tracking branches and tags, with tags peeled to the commit they
+    /// point at
+    refs: HashMap<String, String>,
+    /// Symbolic refs, e.g. `refs/remotes/origin/HEAD` -> `refs/remotes/origin/main`
+    symrefs: HashMap<String, String>,
+    /// Tags that point at HEAD, sorted by name
+    tags: Vec<String>,
+}
+
+impl RepoSnapshot {
+    pub(crate) fn new(git: &impl GitTrait) -> Result<Self, AppError> {
+        let head = match git.get_head()? {
+            GitOutput::Ok(head) => head,
+            GitOutput::Err(_) => {
+                return Err(AppError::new(
+                    ErrorType::MissingGitRepository,
+                    "Looks like you're not in a valid git repository!".to_string(),
+                ))
+            }
+        };
+        let config = match git.get_config_list()? {
+            GitOutput::Ok(config) => config,
+            GitOutput::Err(err) => return Err(AppError::new(ErrorType::CommandFailed, err)),
+        };
+        let refs = match git.get_refs()? {
+            GitOutput::Ok(refs) => refs,
+            GitOutput::Err(err) => return Err(AppError::new(ErrorType::CommandFailed, err)),
+        };
+
+        Ok(Self::parse(&config, &head, &refs))
+    }
+
+    /// Parses the output of the [`GitTrait::get_config_list`], [`GitTrait::get_head`] and
+    /// [`GitTrait::get_refs`] queries
+    pub(crate) fn parse(config: &str, head: &str, refs: &str) -> Self {
+        let config = config
+            .split('\0')
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once('\n') {
+                Some((key, value)) => (key.into(), value.into()),
+                // Keys without a value, e.g. `[core] bare`, are implicitly true
+                None => (entry.into(), "true".into()),
+            })
+            .collect();
+
+        // `<is bare>\n<prefix>\n<commit>\n<full name of HEAD>`, the prefix is empty at the root
+        let mut head = head.lines().rev();
+        let full_name = head.next().unwrap_or_default();
+        let commit = head.next().filter(|commit| !commit.is_empty());
+        let prefix = head.next().unwrap_or_default();
+        let is_bare = head.next() == Some("true");
+
+        let mut snapshot = Self {
+            config,
+            prefix: prefix.into(),
+            is_bare,
+            commit: commit.map(String::from),
+            branch: full_name.strip_prefix("refs/heads/").map(String::from),
+            ..Self::default()
+        };
+
+        // `<name>\0<object>\0<peeled object>\0<symbolic target>`
+        for reference in refs.lines() {
+            let mut fields = reference.split('\0');
+            let (name, object, peeled, symref) = (
+                fields.next().unwrap_or_default(),
+                fields.next().unwrap_or_default(),
+                fields.next().unwrap_or_default(),
+                fields.next().unwrap_or_default(),
+            );
+            let object = if peeled.is_empty() { object } else { peeled };
+
+            if let Some(tag) = name.strip_prefix("refs/tags/") {
+                if snapshot.commit.as_deref() == Some(object) {
+                    snapshot.tags.push(tag.into());
+                }
+            }
+            if !symref.is_empty() {
+                snapshot.symrefs.insert(name.into(), symref.into());
+            }
+            snapshot.refs.insert(name.into(), object.into());
+        }
+        snapshot.tags.sort();
+
+        snapshot
+    }
+
+    /// Mirrors `git config <key>`, where the section and variable name are case-insensitive
+    pub(crate) fn config(&self, key: &str) -> Option<&str> {
+        let key = normalize_key(key);
+        self.config
+            .iter()
+            .rev()
+            .find(|(entry, _)| *entry == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.config
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Remotes with a url, in the order they were configured
+    pub(crate) fn remotes(&self) -> Vec<&str> {
+        let mut remotes = Vec::new();
+        for (key, _) in &self.config {
+            if let Some(remote) = key
+                .strip_prefix("remote.")
+                .and_then(|key| key.strip_suffix(".url"))
+            {
+                if !remotes.contains(&remote) {
+                    remotes.push(remote);
+                }
+            }
+        }
+        remotes
+    }
+
+    pub(crate) fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub(crate) fn is_bare(&self) -> bool {
+        self.is_bare
+    }
+
+    pub(crate) fn current_branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    pub(crate) fn current_commit(&self) -> Option<&str> {
+        self.commit.as_deref()
+    }
+
+    pub(crate) fn current_tag(&self) -> Option<&str> {
+        self.tags.first().map(String::as_str)
+    }
+
+    pub(crate) fn has_tag(&self, tag: &str) -> bool {
+        self.refs.contains_key(&format!("refs/tags/{}", tag))
+    }
+
+    pub(crate) fn has_remote_tracking_branch(&self, remote: &str, branch: &str) -> bool {
+        self.refs
+            .contains_key(&format!("refs/remotes/{}/{}", remote, branch))
+    }
+
+    /// The ref that a ref on the remote is found at locally, branches are looked up in their
+    /// remote-tracking branch, tags and commits as they are
+    pub(crate) fn local_reference(&self, remote: &str, reference: &str) -> String {
+        if self.has_remote_tracking_branch(remote, reference) {
+            format!("refs/remotes/{}/{}", remote, reference)
+        } else {
+            reference.into()
+        }
+    }
+
+    /// Names of the local branches and the remote-tracking branches of the remote, sorted and
+    /// without duplicates
+    pub(crate) fn branches(&self, remote: &str) -> Vec<&str> {
+        let remote_prefix = format!("refs/remotes/{}/", remote);
+        let mut branches = self
+            .refs
+            .keys()
+            .filter_map(|name| {
+                name.strip_prefix("refs/heads/")
+                    .or_else(|| name.strip_prefix(&remote_prefix))
+            })
+            .filter(|branch| *branch != "HEAD")
+            .collect::<Vec<_>>();
+        branches.sort_unstable();
+        branches.dedup();
+        branches
+    }
+
+    /// The branch that `<remote>/HEAD` points at, e.g. `main`
+    pub(crate) fn get_default_branch(&self, remote: &str) -> Option<&str> {
+        self.symrefs
+            .get(&format!("refs/remotes/{}/HEAD", remote))
+            .and_then(|target| target.strip_prefix(&format!("refs/remotes/{}/", remote)))
+    }
+
+    /// The default branch of the remote, falling back to its 'main' or 'master' branch as
+    /// `<remote>/HEAD` is only set when cloning, e.g. not for an added 'upstream'
+    pub(crate) fn find_default_branch(&self, remote: &str) -> Option<&str> {
+        self.get_default_branch(remote).or_else(|| {
+            ["main", "master"]
+                .into_iter()
+                .find(|default_branch| self.has_remote_tracking_branch(remote, default_branch))
+        })
+    }
+
+    /// Mirrors `git ls-remote --get-url <remote>`, rewriting the url with the longest
+    /// matching `url.<base>.insteadOf`
+    pub(crate) fn get_remote_url(&self, remote: &str) -> Option<String> {
+        let url = self.config(&format!("remote.{}.url", remote))?;
+
+        let rewrite = self
+            .config
+            .iter()
+            .filter_map(|(key, prefix)| {
+                let base = key.strip_prefix("url.")?.strip_suffix(".insteadof")?;
+                url.starts_with(prefix.as_str()).then_some((base, prefix))
+            })
+            .max_by_key(|(_, prefix)| prefix.len());
+
+        match rewrite {
+            Some((base, prefix)) => Some(format!("{}{}", base, &url[prefix.len()..])),
+            None => Some(url.into()),
+        }
+    }
+}
+
+/// `git config --list` lowercases the section and variable name but keeps the subsection,
+/// e.g. `branch.Feature.pushRemote` -> `branch.Feature.pushremote`
+fn normalize_key(key: &str) -> String {
+    match (key.split_once('.'), key.rsplit_once('.')) {
+        (Some((section, _)), Some((rest, name))) if rest.len() > section.len() => format!(
+            "{}.{}.{}",
+            section.to_lowercase(),
+            &rest[section.len() + 1..],
+            name.to_lowercase()
+        ),
+        _ => key.to_lowercase(),
+    }
+}